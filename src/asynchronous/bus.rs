@@ -0,0 +1,131 @@
+use core::fmt::Debug;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+use embedded_hal_async::spi::{Operation, SpiDevice};
+
+const CHUNK_SIZE: usize = 256;
+
+/// Asynchronous counterpart of `st_mems_bus::BusOperation`.
+///
+/// Any type implementing this trait can be used as the bus of the asynchronous `Iis2mdc` driver.
+pub trait BusOperation {
+    type Error: Debug;
+
+    fn read_bytes(&mut self, rbuf: &mut [u8]) -> impl Future<Output = Result<(), Self::Error>>;
+    fn write_bytes(&mut self, wbuf: &[u8]) -> impl Future<Output = Result<(), Self::Error>>;
+    fn write_byte_read_bytes(
+        &mut self,
+        wbuf: &[u8; 1],
+        rbuf: &mut [u8],
+    ) -> impl Future<Output = Result<(), Self::Error>>;
+
+    #[inline]
+    fn read_from_register(
+        &mut self,
+        reg: u8,
+        buf: &mut [u8],
+    ) -> impl Future<Output = Result<(), Self::Error>> {
+        async move { self.write_byte_read_bytes(&[reg], buf).await }
+    }
+
+    #[inline]
+    fn write_to_register(
+        &mut self,
+        reg: u8,
+        buf: &[u8],
+    ) -> impl Future<Output = Result<(), Self::Error>> {
+        async move {
+            let mut tmp: [u8; CHUNK_SIZE + 1] = [0; CHUNK_SIZE + 1];
+            let mut reg = reg;
+            for chunk in buf.chunks(CHUNK_SIZE) {
+                tmp[0] = reg;
+                tmp[1..1 + chunk.len()].copy_from_slice(chunk);
+                self.write_bytes(&tmp[..1 + chunk.len()]).await?;
+
+                reg = reg.wrapping_add(chunk.len() as u8);
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Asynchronous I2C bus wrapper.
+pub struct I2cBus<T: I2c> {
+    pub i2c: T,
+    pub address: SevenBitAddress,
+}
+
+impl<T: I2c> I2cBus<T> {
+    /// Creates a new asynchronous I2C bus wrapper.
+    ///
+    /// # Arguments
+    ///
+    /// * `i2c`: The asynchronous I2C peripheral.
+    /// * `address`: The 7-bit I2C address of the sensor.
+    pub fn new(i2c: T, address: SevenBitAddress) -> Self {
+        Self { i2c, address }
+    }
+}
+
+impl<T: I2c> BusOperation for I2cBus<T> {
+    type Error = T::Error;
+
+    #[inline]
+    async fn read_bytes(&mut self, rbuf: &mut [u8]) -> Result<(), Self::Error> {
+        self.i2c.read(self.address, rbuf).await
+    }
+
+    #[inline]
+    async fn write_bytes(&mut self, wbuf: &[u8]) -> Result<(), Self::Error> {
+        self.i2c.write(self.address, wbuf).await
+    }
+
+    #[inline]
+    async fn write_byte_read_bytes(
+        &mut self,
+        wbuf: &[u8; 1],
+        rbuf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.i2c.write_read(self.address, wbuf, rbuf).await
+    }
+}
+
+/// Asynchronous SPI bus wrapper.
+pub struct SpiBus<P> {
+    pub spi: P,
+}
+
+impl<P: SpiDevice> SpiBus<P> {
+    /// Creates a new asynchronous SPI bus wrapper.
+    ///
+    /// # Arguments
+    ///
+    /// * `spi`: The asynchronous SPI device.
+    pub fn new(spi: P) -> Self {
+        Self { spi }
+    }
+}
+
+impl<P: SpiDevice> BusOperation for SpiBus<P> {
+    type Error = P::Error;
+
+    #[inline]
+    async fn read_bytes(&mut self, rbuf: &mut [u8]) -> Result<(), Self::Error> {
+        self.spi.transaction(&mut [Operation::Read(rbuf)]).await
+    }
+
+    #[inline]
+    async fn write_bytes(&mut self, wbuf: &[u8]) -> Result<(), Self::Error> {
+        self.spi.transaction(&mut [Operation::Write(wbuf)]).await
+    }
+
+    #[inline]
+    async fn write_byte_read_bytes(
+        &mut self,
+        wbuf: &[u8; 1],
+        rbuf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.spi
+            .transaction(&mut [Operation::Write(&[wbuf[0] | 0x80]), Operation::Read(rbuf)])
+            .await
+    }
+}
@@ -0,0 +1,1839 @@
+//! Asynchronous driver built on top of `embedded-hal-async`.
+//!
+//! The API mirrors the blocking [`crate::Iis2mdc`] driver, with every bus access exposed as an
+//! `async fn`. This module is only available when the `async` feature is enabled.
+
+use crate::calibration::SoftIronFit;
+use crate::prelude::*;
+use crate::self_test::SELF_TEST_SETTLING_MS;
+use crate::{
+    BOOT_TIME_MS, CalibrationData, Config, DRDY_POLL_DELAY_MS, Error, FifoSample, HardIronFit,
+    I2CAddress, IIS2MDC_ID, INIT_MAX_RETRIES, INIT_POLL_DELAY_MS, MagneticField, POWER_UP_TIME_MS,
+    RAW_SAMPLE_LEN, RawSample, RegisterSnapshot, SELF_TEST_DRDY_TIMEOUT_MS, SELF_TEST_SAMPLES,
+    SINGLE_MEASUREMENT_TIMEOUT_MS, SNAPSHOT_BLOCK_LEN, SampleFifo, SelfTestReport, ThresholdConfig,
+    ThresholdEvent, WAKE_AMBIENT_SAMPLES, WakeOnMagnet, from_lsb_to_celsius, odr_period_ms,
+};
+use embedded_hal::digital::Error as _;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use embedded_hal_async::i2c::{I2c, SevenBitAddress};
+use embedded_hal_async::spi::SpiDevice;
+
+pub mod bus;
+pub mod stream;
+
+pub use bus::{BusOperation, I2cBus, SpiBus};
+pub use stream::SampleStream;
+
+/// The Iis2mdc generic asynchronous driver struct.
+pub struct Iis2mdc<B, T> {
+    /// The bus driver.
+    pub bus: B,
+    pub tim: T,
+}
+
+/// Register layouts that can be transferred over the asynchronous bus.
+trait AsyncRegister: Sized {
+    const ADDRESS: Reg;
+    type Bytes: AsRef<[u8]> + AsMut<[u8]> + Default;
+
+    fn from_bytes(bytes: Self::Bytes) -> Self;
+    fn to_bytes(&self) -> Self::Bytes;
+}
+
+macro_rules! async_register {
+    ($reg:ty, $address:expr, u8) => {
+        impl AsyncRegister for $reg {
+            const ADDRESS: Reg = $address;
+            type Bytes = [u8; 1];
+
+            fn from_bytes(bytes: Self::Bytes) -> Self {
+                Self::from_bits(bytes[0])
+            }
+
+            fn to_bytes(&self) -> Self::Bytes {
+                [self.into_bits()]
+            }
+        }
+    };
+    ($reg:ty, $address:expr, u16) => {
+        impl AsyncRegister for $reg {
+            const ADDRESS: Reg = $address;
+            type Bytes = [u8; 2];
+
+            fn from_bytes(bytes: Self::Bytes) -> Self {
+                Self::from_bits(u16::from_le_bytes(bytes))
+            }
+
+            fn to_bytes(&self) -> Self::Bytes {
+                self.into_bits().to_le_bytes()
+            }
+        }
+    };
+    ($reg:ty, $address:expr, named) => {
+        impl AsyncRegister for $reg {
+            const ADDRESS: Reg = $address;
+            type Bytes = [u8; 6];
+
+            fn from_bytes(bytes: Self::Bytes) -> Self {
+                Self::from_le_bytes(bytes)
+            }
+
+            fn to_bytes(&self) -> Self::Bytes {
+                self.to_le_bytes()
+            }
+        }
+    };
+}
+
+async_register!(CfgRegA, Reg::CfgRegA, u8);
+async_register!(CfgRegB, Reg::CfgRegB, u8);
+async_register!(CfgRegC, Reg::CfgRegC, u8);
+async_register!(IntCtrlReg, Reg::IntCtrlReg, u8);
+async_register!(IntSourceReg, Reg::IntSourceReg, u8);
+async_register!(StatusReg, Reg::StatusReg, u8);
+async_register!(TempOutReg, Reg::TempOutLReg, u16);
+async_register!(IntThsReg, Reg::IntThsLReg, u16);
+async_register!(OffsetXYZ, Reg::OffsetXRegL, named);
+async_register!(OutXYZ, Reg::OutxLReg, named);
+
+impl<P, T> Iis2mdc<I2cBus<P>, T>
+where
+    P: I2c,
+    T: DelayNs,
+{
+    /// Constructor method for using the asynchronous I2C bus.
+    ///
+    /// # Arguments
+    ///
+    /// * `i2c`: The I2C peripheral.
+    /// * `address`: The I2C address of the COMPONENT sensor.
+    /// * `tim`: The timer of the COMPONENT sensor.
+    ///
+    /// # Returns
+    ///
+    /// * `Self`: Returns an instance of `Iis2mdc`.
+    pub fn new_i2c(i2c: P, address: I2CAddress, tim: T) -> Self {
+        // Initialize the I2C bus with the COMPONENT address
+        let bus = I2cBus::new(i2c, address as SevenBitAddress);
+        Self { bus, tim }
+    }
+}
+
+impl<B, T> Iis2mdc<B, T>
+where
+    B: BusOperation,
+    T: DelayNs,
+{
+    /// Create an instance of the driver from any bus implementing the asynchronous
+    /// [`BusOperation`].
+    ///
+    /// # Arguments
+    ///
+    /// * `bus`: The bus that implements BusOperation.
+    /// * `tim`: The timer of the COMPONENT sensor.
+    ///
+    /// # Returns
+    ///
+    /// * `Self`: Returns an instance of `Iis2mdc`.
+    pub fn from_bus(bus: B, tim: T) -> Self {
+        Self { bus, tim }
+    }
+}
+
+impl<P, T> Iis2mdc<SpiBus<P>, T>
+where
+    P: SpiDevice,
+    T: DelayNs,
+{
+    /// Constructor method for using the asynchronous SPI bus.
+    ///
+    /// # Arguments
+    ///
+    /// * `spi`: The SPI peripheral.
+    /// * `tim`: The timer of the COMPONENT sensor.
+    ///
+    /// # Returns
+    ///
+    /// * `Self`: Returns an instance of `Iis2mdc`.
+    pub fn new_spi(spi: P, tim: T) -> Self {
+        // Initialize the SPI bus
+        let bus = SpiBus::new(spi);
+        Self { bus, tim }
+    }
+}
+
+impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
+    pub async fn read_from_register(
+        &mut self,
+        reg: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Error<B::Error>> {
+        self.bus
+            .read_from_register(reg, buf)
+            .await
+            .map_err(Error::Bus)
+    }
+
+    pub async fn write_to_register(&mut self, reg: u8, buf: &[u8]) -> Result<(), Error<B::Error>> {
+        self.bus
+            .write_to_register(reg, buf)
+            .await
+            .map_err(Error::Bus)
+    }
+
+    async fn read_reg<R: AsyncRegister>(&mut self) -> Result<R, Error<B::Error>> {
+        let mut buff = R::Bytes::default();
+        self.read_from_register(R::ADDRESS as u8, buff.as_mut())
+            .await?;
+        Ok(R::from_bytes(buff))
+    }
+
+    async fn write_reg<R: AsyncRegister>(&mut self, reg: &R) -> Result<(), Error<B::Error>> {
+        self.write_to_register(R::ADDRESS as u8, reg.to_bytes().as_ref())
+            .await
+    }
+
+    /// Sets the magnetic sensor's hard-iron offset to compensate for environmental effects.
+    ///
+    /// This function writes a set of three 16-bit values to the sensor's registers, which represent
+    /// the hard-iron offset. These offsets are used to adjust the magnetic output data, effectively
+    /// removing environmental biases. The data format for these values is two's complement, with
+    /// a resolution of 1LSb = 1.5mG.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: A reference to an array containing three 16-bit integers. Each integer represents
+    ///   the offset for one axis (X, Y, Z) of the magnetic sensor. The values are expected to be
+    ///   in two's complement format.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the offsets are successfully written
+    ///   to the sensor's registers. If the operation fails, it returns an `Err` containing the
+    ///   specific error type generated by the microcontroller's HAL.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation
+    pub async fn mag_user_offset_set(&mut self, val: &[i16; 3]) -> Result<(), Error<B::Error>> {
+        let reg = OffsetXYZ {
+            x: val[0],
+            y: val[1],
+            z: val[2],
+        };
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the magnetic sensor's hard-iron offset values.
+    ///
+    /// This function reads a set of three 16-bit values from the sensor's registers, which represent
+    /// the hard-iron offset. These offsets are used to adjust the magnetic output data, effectively
+    /// removing environmental biases. The data format for these values is two's complement, with
+    /// a resolution of 1LSb = 1.5mG.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<[i16; 3], Error<B::Error>>`: Returns an array of three 16-bit integers if the
+    ///   offsets are successfully read from the sensor's registers. Each integer represents the
+    ///   offset for one axis (X, Y, Z) of the magnetic sensor.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation
+    pub async fn mag_user_offset_get(&mut self) -> Result<[i16; 3], Error<B::Error>> {
+        let val = self.read_reg::<OffsetXYZ>().await?;
+        Ok([val.x, val.y, val.z])
+    }
+
+    /// Applies a hard-iron fit to the offset registers.
+    ///
+    /// This function reads the hard-iron offset currently programmed in the `OFFSET_X/Y/Z_REG`
+    /// registers, adds the offset estimated by the fit and writes the result with
+    /// `mag_user_offset_set`. The samples used for the fit must have been collected with the
+    /// current offset in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `fit`: The result of `HardIronCalibrator::fit`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<[i16; 3], Error<B::Error>>`: Returns the offset written to the sensor, in LSB (1 LSB = 1.5 mG). Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn hard_iron_offset_apply(
+        &mut self,
+        fit: &HardIronFit,
+    ) -> Result<[i16; 3], Error<B::Error>> {
+        let current = self.mag_user_offset_get().await?;
+        let mut offset = [0; 3];
+        (0..3).for_each(|axis| offset[axis] = current[axis].saturating_add(fit.offset[axis]));
+        self.mag_user_offset_set(&offset).await?;
+
+        Ok(offset)
+    }
+
+    /// Reads the calibration currently in use into a [`CalibrationData`] record.
+    ///
+    /// This function reads the device ID, the hard-iron offset from the `OFFSET_X/Y/Z_REG`
+    /// registers and the current temperature, and stores them together with the soft-iron matrix
+    /// and offset computed by the application.
+    ///
+    /// # Arguments
+    ///
+    /// * `soft_iron`: The soft-iron correction, fitted on samples taken with the current hard-iron
+    ///   offset. Use `SoftIronFit::default()` with an identity matrix if there is none.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<CalibrationData, Error<B::Error>>`: Returns the calibration record. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn calibration_data_get(
+        &mut self,
+        soft_iron: &SoftIronFit,
+    ) -> Result<CalibrationData, Error<B::Error>> {
+        Ok(CalibrationData {
+            sensor_id: self.device_id_get().await?,
+            hard_iron: self.mag_user_offset_get().await?,
+            soft_iron: soft_iron.matrix,
+            soft_iron_offset: soft_iron.offset,
+            temperature: self.temperature_get().await?,
+        })
+    }
+
+    /// Restores a stored calibration.
+    ///
+    /// This function reads the device ID from the `WHO_AM_I` register, checks that it matches the
+    /// sensor ID stored in the record and writes the hard-iron offset back through
+    /// `mag_user_offset_set`. The soft-iron correction is applied in software with
+    /// `CalibrationData::soft_iron_fit` and is not written to the sensor.
+    ///
+    /// The sensor ID identifies the part type, not the unit: a record taken from another IIS2MDC is
+    /// accepted. Keeping the calibration with the board it was taken on is up to the application.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The calibration record, e.g. from `CalibrationData::deserialize`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the offset is successfully written. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::SensorIdMismatch { stored, device }`: This error is returned if the record was stamped with a sensor ID different from the device ID.
+    pub async fn restore_calibration(
+        &mut self,
+        val: &CalibrationData,
+    ) -> Result<(), Error<B::Error>> {
+        let device = self.device_id_get().await?;
+        if val.sensor_id != device {
+            return Err(Error::SensorIdMismatch {
+                stored: val.sensor_id,
+                device,
+            });
+        }
+        self.mag_user_offset_set(&val.hard_iron).await
+    }
+
+    /// Sets the operating mode of the sensor.
+    ///
+    /// This function modifies the operating mode by updating the `md` field in the `CFG_REG_A` register.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired operating mode, represented by the `Md` enum.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the mode is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn operating_mode_set(&mut self, val: Md) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegA>().await?;
+        reg.set_md(val as u8);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current operating mode of the sensor.
+    ///
+    /// This function reads the `md` field from the `CFG_REG_A` register to determine the current operating mode.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Md, Error<B::Error>>`: Returns the current operating mode as an `Md` enum. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
+    pub async fn operating_mode_get(&mut self) -> Result<Md, Error<B::Error>> {
+        let val = self.read_reg::<CfgRegA>().await?.md();
+        Md::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegA,
+            value: val,
+        })
+    }
+
+    /// Sets the output data rate of the sensor.
+    ///
+    /// This function modifies the output data rate by updating the `odr` field in the `CFG_REG_A` register.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired data rate, represented by the `Odr` enum.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the data rate is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn data_rate_set(&mut self, val: Odr) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegA>().await?;
+        reg.set_odr(val as u8);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current output data rate of the sensor.
+    ///
+    /// This function reads the `odr` field from the `CFG_REG_A` register to determine the current data rate.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Odr, Error<B::Error>>`: Returns the current data rate as an `Odr` enum. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
+    pub async fn data_rate_get(&mut self) -> Result<Odr, Error<B::Error>> {
+        let val = self.read_reg::<CfgRegA>().await?.odr();
+        Odr::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegA,
+            value: val,
+        })
+    }
+
+    /// Sets the power mode of the sensor to high-resolution or low-power.
+    ///
+    /// This function modifies the power mode by updating the `lp` field in the `CFG_REG_A` register.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired power mode, represented by the `Lp` enum.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the power mode is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn power_mode_set(&mut self, val: Lp) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegA>().await?;
+        reg.set_lp(val as u8);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current power mode of the sensor.
+    ///
+    /// This function reads the `lp` field from the `CFG_REG_A` register to determine the current power mode.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Lp, Error<B::Error>>`: Returns the current power mode as an `Lp` enum. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
+    pub async fn power_mode_get(&mut self) -> Result<Lp, Error<B::Error>> {
+        let val = self.read_reg::<CfgRegA>().await?.lp();
+        Lp::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegA,
+            value: val,
+        })
+    }
+
+    /// Enables or disables the magnetometer temperature compensation.
+    ///
+    /// This function modifies the temperature compensation setting by updating the `comp_temp_en` field in the `CFG_REG_A` register.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired state of temperature compensation (enabled or disabled).
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the setting is successfully updated. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn offset_temp_comp_set(&mut self, val: u8) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegA>().await?;
+        reg.set_comp_temp_en(val);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current state of the magnetometer temperature compensation.
+    ///
+    /// This function reads the `comp_temp_en` field from the `CFG_REG_A` register to determine if temperature compensation is enabled or disabled.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u8, Error<B::Error>>`: Returns the current state of temperature compensation. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn offset_temp_comp_get(&mut self) -> Result<u8, Error<B::Error>> {
+        Ok(self.read_reg::<CfgRegA>().await?.comp_temp_en())
+    }
+
+    /// Sets the low-pass filter bandwidth of the sensor.
+    ///
+    /// This function modifies the low-pass filter bandwidth by updating the `lpf` field in the `CFG_REG_B` register.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired low-pass filter bandwidth, represented by the `Lpf` enum.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the bandwidth is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn low_pass_bandwidth_set(&mut self, val: Lpf) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegB>().await?;
+        reg.set_lpf(val as u8);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current low-pass filter bandwidth of the sensor.
+    ///
+    /// This function reads the `lpf` field from the `CFG_REG_B` register to determine the current bandwidth setting.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Lpf, Error<B::Error>>`: Returns the current low-pass filter bandwidth as an `Lpf` enum. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
+    pub async fn low_pass_bandwidth_get(&mut self) -> Result<Lpf, Error<B::Error>> {
+        let val = self.read_reg::<CfgRegB>().await?.lpf();
+        Lpf::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegB,
+            value: val,
+        })
+    }
+
+    /// Sets the reset pulse mode.
+    ///
+    /// This function modifies the reset pulse mode by updating the `set_rst` field in the `CFG_REG_B` register.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired reset pulse mode, represented by the `SetRst` enum.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the mode is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn set_rst_mode_set(&mut self, val: SetRst) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegB>().await?;
+        reg.set_set_rst(val as u8);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current reset pulse mode.
+    ///
+    /// This function reads the `set_rst` field from the `CFG_REG_B` register to determine the current reset pulse mode.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SetRst, Error<B::Error>>`: Returns the current reset pulse mode as a `SetRst` enum. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
+    pub async fn set_rst_mode_get(&mut self) -> Result<SetRst, Error<B::Error>> {
+        let val = self.read_reg::<CfgRegB>().await?.set_rst();
+        SetRst::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegB,
+            value: val,
+        })
+    }
+
+    /// Enables offset cancellation in single measurement mode.
+    ///
+    /// This function modifies the offset cancellation setting by updating the `off_canc_one_shot` field in the `CFG_REG_B` register.
+    /// The `OFF_CANC` bit must be set to 1, which requires calling the function `set_rst_mode(SensOffCancEveryOdr)`.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired state of offset cancellation (enabled or disabled).
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the setting is successfully updated. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn off_canc_en_set(&mut self, val: u8) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegB>().await?;
+        reg.set_off_canc_one_shot(val);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current state of offset cancellation in single measurement mode.
+    ///
+    /// This function reads the `off_canc_one_shot` field from the `CFG_REG_B` register to determine if offset cancellation is enabled or disabled.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u8, Error<B::Error>>`: Returns the current state of offset cancellation. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn off_canc_en_get(&mut self) -> Result<u8, Error<B::Error>> {
+        Ok(self.read_reg::<CfgRegB>().await?.off_canc_one_shot())
+    }
+
+    /// Sets the block data update mode.
+    ///
+    /// This function modifies the block data update setting by updating the `bdu` field in the `CFG_REG_C` register.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired state of block data update (enabled or disabled).
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the setting is successfully updated. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn block_data_update_set(&mut self, val: u8) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegC>().await?;
+        reg.set_bdu(val);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current block data update mode.
+    ///
+    /// This function reads the `bdu` field from the `CFG_REG_C` register to determine if block data update is enabled or disabled.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u8, Error<B::Error>>`: Returns the current state of block data update. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn block_data_update_get(&mut self) -> Result<u8, Error<B::Error>> {
+        Ok(self.read_reg::<CfgRegC>().await?.bdu())
+    }
+
+    /// Checks if magnetic data is ready.
+    ///
+    /// This function reads the `zyxda` field from the `STATUS_REG` register to determine if a new set of magnetic data is available.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u8, Error<B::Error>>`: Returns the state indicating if magnetic data is ready. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn mag_data_ready_get(&mut self) -> Result<u8, Error<B::Error>> {
+        Ok(self.read_reg::<StatusReg>().await?.zyxda())
+    }
+
+    /// Checks if magnetic data has overrun.
+    ///
+    /// This function reads the `zyxor` field from the `STATUS_REG` register to determine if magnetic data has overrun.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u8, Error<B::Error>>`: Returns the state indicating if magnetic data has overrun. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn mag_data_ovr_get(&mut self) -> Result<u8, Error<B::Error>> {
+        Ok(self.read_reg::<StatusReg>().await?.zyxor())
+    }
+
+    /// Waits until a new set of magnetic data is available.
+    ///
+    /// This function polls the `zyxda` field of the `STATUS_REG` register, waiting 1 ms between
+    /// two consecutive reads, until new data is available or the timeout expires.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout_ms`: The maximum time to wait, in milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` as soon as new data is available. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::Timeout`: This error is returned if no new data is available within `timeout_ms`.
+    pub async fn mag_data_ready_wait(&mut self, timeout_ms: u32) -> Result<(), Error<B::Error>> {
+        let mut elapsed = 0;
+        while self.mag_data_ready_get().await? == 0 {
+            if elapsed >= timeout_ms {
+                return Err(Error::Timeout);
+            }
+            self.tim.delay_ms(DRDY_POLL_DELAY_MS).await;
+            elapsed += DRDY_POLL_DELAY_MS;
+        }
+
+        Ok(())
+    }
+
+    // Retrieves the raw magnetic output values.
+    ///
+    /// This function reads the magnetic output values from the sensor's registers, returning them as a three-element array of 16-bit integers.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<[i16; 3], Error<B::Error>>`: Returns an array containing the raw magnetic output values. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn magnetic_raw_get(&mut self) -> Result<[i16; 3], Error<B::Error>> {
+        let val = self.read_reg::<OutXYZ>().await?;
+        Ok([val.x, val.y, val.z])
+    }
+
+    /// Retrieves the magnetic field in milligauss.
+    ///
+    /// This function reads the magnetic output values from the sensor's registers and applies the
+    /// 1.5 mG/LSB sensitivity.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<MagneticField, Error<B::Error>>`: Returns the magnetic field on the X, Y, Z axes. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn magnetic_get(&mut self) -> Result<MagneticField, Error<B::Error>> {
+        Ok(MagneticField::from_raw(self.magnetic_raw_get().await?))
+    }
+
+    //// Retrieves the raw temperature output value.
+    ///
+    /// This function reads the temperature value from the sensor's registers, returning it as a 16-bit integer.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<i16, Error<B::Error>>`: Returns the raw temperature value. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn temperature_raw_get(&mut self) -> Result<i16, Error<B::Error>> {
+        Ok(self.read_reg::<TempOutReg>().await?.temp_out())
+    }
+
+    /// Retrieves the temperature in degrees Celsius.
+    ///
+    /// This function reads the temperature output register and converts it with [`from_lsb_to_celsius`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<f32, Error<B::Error>>`: Returns the temperature in degrees Celsius. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn temperature_get(&mut self) -> Result<f32, Error<B::Error>> {
+        Ok(from_lsb_to_celsius(self.temperature_raw_get().await?))
+    }
+
+    /// Retrieves the status, magnetic and temperature output values in a single burst.
+    ///
+    /// This function reads the registers from `STATUS_REG` (0x67) to `TEMP_OUT_H_REG` (0x6F) in
+    /// one bus transaction, instead of one transaction each for `status_get`,
+    /// `magnetic_raw_get` and `temperature_raw_get`. The temperature is guaranteed to belong to
+    /// the same sample as the field.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RawSample, Error<B::Error>>`: Returns the raw status, magnetic and temperature values. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn sample_raw_get(&mut self) -> Result<RawSample, Error<B::Error>> {
+        let mut buff = [0u8; RAW_SAMPLE_LEN];
+        self.read_from_register(Reg::StatusReg as u8, &mut buff)
+            .await?;
+
+        Ok(RawSample::from_bytes(buff))
+    }
+
+    /// Returns an asynchronous stream of the samples of the sensor.
+    ///
+    /// This function reads the output data rate from the `CFG_REG_A` register to pace the
+    /// stream: each sample is awaited by polling the `STATUS_REG` register a few times per
+    /// sample period, sleeping with the delay of the driver in between. The device must be in
+    /// continuous mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `temperature`: Whether to report the temperature with each sample.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SampleStream<'_, B, T>, Error<B::Error>>`: Returns the stream. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
+    pub async fn samples(
+        &mut self,
+        temperature: bool,
+    ) -> Result<SampleStream<'_, B, T>, Error<B::Error>> {
+        let period_ms = odr_period_ms(self.data_rate_get().await?);
+        Ok(SampleStream {
+            sensor: self,
+            period_ms,
+            temperature,
+        })
+    }
+
+    /// Retrieves the device ID.
+    ///
+    /// This function reads the device ID from the sensor's registers, returning it as an 8-bit integer.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u8, Error<B::Error>>`: Returns the device ID. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn device_id_get(&mut self) -> Result<u8, Error<B::Error>> {
+        let mut buff: [u8; 1] = [0];
+        self.read_from_register(Reg::WhoAmI as u8, &mut buff)
+            .await?;
+
+        Ok(buff[0])
+    }
+
+    /// Performs a software reset, restoring default values in user registers.
+    ///
+    /// This function modifies the `soft_rst` field in the `CFG_REG_A` register to initiate a software reset.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The value to set for the software reset.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the reset is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn reset_set(&mut self, val: u8) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegA>().await?;
+        reg.set_soft_rst(val);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current software reset state.
+    ///
+    /// This function reads the `soft_rst` field from the `CFG_REG_A` register to determine the current reset state.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u8, Error<B::Error>>`: Returns the current software reset state. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn reset_get(&mut self) -> Result<u8, Error<B::Error>> {
+        Ok(self.read_reg::<CfgRegA>().await?.soft_rst())
+    }
+
+    /// Reboots memory content, reloading calibration parameters.
+    ///
+    /// This function modifies the `reboot` field in the `CFG_REG_A` register to initiate a memory reboot.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The value to set for the memory reboot.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the reboot is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn boot_set(&mut self, val: u8) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegA>().await?;
+        reg.set_reboot(val);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current memory reboot state.
+    ///
+    /// This function reads the `reboot` field from the `CFG_REG_A` register to determine the current reboot state.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u8, Error<B::Error>>`: Returns the current memory reboot state. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn boot_get(&mut self) -> Result<u8, Error<B::Error>> {
+        Ok(self.read_reg::<CfgRegA>().await?.reboot())
+    }
+
+    /// Sets the self-test mode.
+    ///
+    /// This function modifies the `self_test` field in the `CFG_REG_C` register to initiate a self-test.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The value to set for the self-test mode.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the self-test mode is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn self_test_set(&mut self, val: u8) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegC>().await?;
+        reg.set_self_test(val);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current self-test mode.
+    ///
+    /// This function reads the `self_test` field from the `CFG_REG_C` register to determine the current self-test mode.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u8, Error<B::Error>>`: Returns the current self-test mode. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn self_test_get(&mut self) -> Result<u8, Error<B::Error>> {
+        Ok(self.read_reg::<CfgRegC>().await?.self_test())
+    }
+
+    /// Sets the data format to Big or Little Endian.
+    ///
+    /// This function modifies the `ble` field in the `CFG_REG_C` register to select the data format.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired data format, represented by the `Ble` enum.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the data format is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn data_format_set(&mut self, val: Ble) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegC>().await?;
+        reg.set_ble(val as u8);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current data format (Big or Little Endian).
+    ///
+    /// This function reads the `ble` field from the `CFG_REG_C` register to determine the current data format.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Ble, Error<B::Error>>`: Returns the current data format as a `Ble` enum. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
+    pub async fn data_format_get(&mut self) -> Result<Ble, Error<B::Error>> {
+        let val = self.read_reg::<CfgRegC>().await?.ble();
+        Ble::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegC,
+            value: val,
+        })
+    }
+
+    /// Retrieves the device status information.
+    ///
+    /// This function reads the `STATUS_REG` register to obtain the current status of the device.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<StatusReg, Error<B::Error>>`: Returns the status register value. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn status_get(&mut self) -> Result<StatusReg, Error<B::Error>> {
+        self.read_reg::<StatusReg>().await
+    }
+
+    /// Reads a new magnetic sample, if any, into a software FIFO.
+    ///
+    /// This function reads the status and output registers in a single burst and, when a new set
+    /// of data is available (`zyxda`), pushes the sample to the queue, flagged with the data
+    /// overrun bit (`zyxor`). It is meant to be called on each data-ready event, e.g. from
+    /// the INT/DRDY pin interrupt.
+    ///
+    /// # Arguments
+    ///
+    /// * `fifo`: The queue receiving the sample.
+    /// * `timestamp`: The timestamp stored with the sample, in the application time base.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool, Error<B::Error>>`: Returns `true` if a sample was pushed, `false` if no new data was available. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn sample_fifo_update<const N: usize>(
+        &mut self,
+        fifo: &mut SampleFifo<N>,
+        timestamp: u64,
+    ) -> Result<bool, Error<B::Error>> {
+        let sample = self.sample_raw_get().await?;
+        if sample.status.zyxda() == 0 {
+            return Ok(false);
+        }
+
+        fifo.push(FifoSample {
+            raw: sample.magnetic,
+            timestamp,
+            overrun: sample.status.zyxor() == 1,
+        });
+
+        Ok(true)
+    }
+
+    /// Configures the interrupt block recognition for data checks after/before hard-iron correction.
+    ///
+    /// This function modifies the `int_on_dataoff` field in the `CFG_REG_B` register to set the interrupt configuration.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired interrupt configuration, represented by the `IntOnDataOff` enum.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the configuration is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn offset_int_conf_set(&mut self, val: IntOnDataOff) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegB>().await?;
+        reg.set_int_on_dataoff(val as u8);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current interrupt configuration for data checks after/before hard-iron correction.
+    ///
+    /// This function reads the `int_on_dataoff` field from the `CFG_REG_B` register to determine the current configuration.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<IntOnDataOff, Error<B::Error>>`: Returns the current interrupt configuration as an `IntOnDataOff` enum. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
+    pub async fn offset_int_conf_get(&mut self) -> Result<IntOnDataOff, Error<B::Error>> {
+        let val = self.read_reg::<CfgRegB>().await?.int_on_dataoff();
+        IntOnDataOff::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegB,
+            value: val,
+        })
+    }
+
+    /// Sets the data-ready signal on the INT_DRDY pin.
+    ///
+    /// This function modifies the `drdy_on_pin` field in the `CFG_REG_C` register to enable or disable the data-ready signal.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired state of the data-ready signal (enabled or disabled).
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the signal is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn drdy_on_pin_set(&mut self, val: u8) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegC>().await?;
+        reg.set_drdy_on_pin(val);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current state of the data-ready signal on the INT_DRDY pin.
+    ///
+    /// This function reads the `drdy_on_pin` field from the `CFG_REG_C` register to determine if the data-ready signal is enabled or disabled.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u8, Error<B::Error>>`: Returns the current state of the data-ready signal. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn drdy_on_pin_get(&mut self) -> Result<u8, Error<B::Error>> {
+        Ok(self.read_reg::<CfgRegC>().await?.drdy_on_pin())
+    }
+
+    /// Sets the interrupt signal on the INT_DRDY pin.
+    ///
+    /// This function modifies the `int_on_pin` field in the `CFG_REG_C` register to enable or disable the interrupt signal.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired state of the interrupt signal (enabled or disabled).
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the signal is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn int_on_pin_set(&mut self, val: u8) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegC>().await?;
+        reg.set_int_on_pin(val);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current state of the interrupt signal on the INT_DRDY pin.
+    ///
+    /// This function reads the `int_on_pin` field from the `CFG_REG_C` register to determine if the interrupt signal is enabled or disabled.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<u8, Error<B::Error>>`: Returns the current state of the interrupt signal. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn int_on_pin_get(&mut self) -> Result<u8, Error<B::Error>> {
+        Ok(self.read_reg::<CfgRegC>().await?.int_on_pin())
+    }
+
+    /// Sets the interrupt generator configuration.
+    ///
+    /// This function writes to the `INT_CTRL_REG` register to configure the interrupt generator settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired configuration for the interrupt generator, represented by the `IntCtrlReg` struct.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the configuration is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn int_gen_conf_set(&mut self, val: IntCtrlReg) -> Result<(), Error<B::Error>> {
+        self.write_reg(&val).await
+    }
+
+    /// Retrieves the current interrupt generator configuration.
+    ///
+    /// This function reads the `INT_CTRL_REG` register to obtain the current configuration of the interrupt generator.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<IntCtrlReg, Error<B::Error>>`: Returns the current configuration as an `IntCtrlReg` struct. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn int_gen_conf_get(&mut self) -> Result<IntCtrlReg, Error<B::Error>> {
+        self.read_reg::<IntCtrlReg>().await
+    }
+
+    /// Retrieves the interrupt generator source register value.
+    ///
+    /// This function reads the `INT_SOURCE_REG` register to obtain the source of the interrupt generator.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<IntSourceReg, Error<B::Error>>`: Returns the source register value as an `IntSourceReg` struct. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn int_gen_source_get(&mut self) -> Result<IntSourceReg, Error<B::Error>> {
+        self.read_reg::<IntSourceReg>().await
+    }
+
+    /// Sets the user-defined threshold value for the XL interrupt event on the generator.
+    ///
+    /// This function writes the threshold value to the `INT_THS_L_REG` register. The data format is two's complement with 1LSb = 1.5mG.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The threshold value to set, represented as a 16-bit integer.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the threshold is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation..
+    pub async fn int_gen_threshold_set(&mut self, val: i16) -> Result<(), Error<B::Error>> {
+        self.write_reg(&IntThsReg::from_bits(val.cast_unsigned()))
+            .await
+    }
+
+    /// Retrieves the user-defined threshold value for the XL interrupt event on the generator.
+    ///
+    /// This function reads the threshold value from the `INT_THS_L_REG` register. The data format is two's complement with 1LSb = 1.5mG.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<i16, Error<B::Error>>`: Returns the threshold value as a 16-bit integer. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn int_gen_threshold_get(&mut self) -> Result<i16, Error<B::Error>> {
+        Ok(self.read_reg::<IntThsReg>().await?.int_ths())
+    }
+
+    /// Configures the threshold interrupt generator.
+    ///
+    /// This function writes the threshold to the `INT_THS_L/H_REG` registers, converted from
+    /// milligauss to LSB, and then the per-axis enables, latch, polarity and generator enable to
+    /// the `INT_CTRL_REG` register. Routing the interrupt to the INT/DRDY pin is controlled
+    /// separately with `int_on_pin_set`.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The threshold interrupt configuration, represented by the `ThresholdConfig` struct.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the configuration is successfully written. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidConfiguration`: This error is returned if the configuration is not valid.
+    pub async fn threshold_event_set(
+        &mut self,
+        val: &ThresholdConfig,
+    ) -> Result<(), Error<B::Error>> {
+        val.validate()?;
+        let (ctrl, ths) = val.to_registers();
+        self.write_reg(&ths).await?;
+        self.write_reg(&ctrl).await
+    }
+
+    /// Retrieves the threshold interrupt generator configuration.
+    ///
+    /// This function reads the `INT_CTRL_REG` and `INT_THS_L/H_REG` registers.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ThresholdConfig, Error<B::Error>>`: Returns the current configuration. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
+    pub async fn threshold_event_get(&mut self) -> Result<ThresholdConfig, Error<B::Error>> {
+        let ctrl = self.read_reg::<IntCtrlReg>().await?;
+        let ths = self.read_reg::<IntThsReg>().await?;
+        ThresholdConfig::from_registers(ctrl, ths)
+    }
+
+    /// Retrieves the decoded threshold interrupt event.
+    ///
+    /// This function reads the `INT_SOURCE_REG` register. When the interrupt is latched, reading
+    /// it also clears the interrupt.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ThresholdEvent, Error<B::Error>>`: Returns the decoded event. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn threshold_event_source_get(&mut self) -> Result<ThresholdEvent, Error<B::Error>> {
+        Ok(ThresholdEvent::from_register(
+            self.int_gen_source_get().await?,
+        ))
+    }
+
+    /// Enables or disables the I2C interface.
+    ///
+    /// This function modifies the `i2c_dis` field in the `CFG_REG_C` register to enable or disable the I2C interface.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired state of the I2C interface, represented by the `I2cDis` enum.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the interface is successfully set. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn i2c_interface_set(&mut self, val: I2cDis) -> Result<(), Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegC>().await?;
+        reg.set_i2c_dis(val as u8);
+        self.write_reg(&reg).await
+    }
+
+    /// Retrieves the current state of the I2C interface.
+    ///
+    /// This function reads the `i2c_dis` field from the `CFG_REG_C` register to determine if the I2C interface is enabled or disabled.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<I2cDis, Error<B::Error>>`: Returns the current state of the I2C interface as an `I2cDis` enum. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
+    pub async fn i2c_interface_get(&mut self) -> Result<I2cDis, Error<B::Error>> {
+        let val = self.read_reg::<CfgRegC>().await?.i2c_dis();
+        I2cDis::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegC,
+            value: val,
+        })
+    }
+
+    /// Triggers a single measurement and returns its raw magnetic output values.
+    ///
+    /// This function discards any pending sample, writes the `off_canc_one_shot` field of the
+    /// `CFG_REG_B` register, triggers a measurement by setting the operating mode to
+    /// `Md::SingleTrigger` and waits for the `zyxda` field of the `STATUS_REG` register. After the
+    /// measurement, the device automatically goes back to idle mode.
+    ///
+    /// Offset cancellation in single measurement mode requires the `set_rst` field to be set to
+    /// `SetRst::SensOffCancEveryOdr` in the same write as `off_canc_one_shot`. The previous content
+    /// of `CFG_REG_B` is written back once the measurement is over, whether it succeeded or not, so
+    /// the `set_rst` mode configured by the application is left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `off_canc`: `1` to cancel the offset for this measurement, `0` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<[i16; 3], Error<B::Error>>`: Returns the raw X, Y, Z values of the triggered measurement, in LSB (1 LSB = 1.5 mG). Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidConfiguration`: This error is returned if `off_canc` is neither `0` nor `1`.
+    /// - `Error::Timeout`: This error is returned if the measurement does not complete in time.
+    pub async fn measure_once(&mut self, off_canc: u8) -> Result<[i16; 3], Error<B::Error>> {
+        if off_canc > 1 {
+            return Err(Error::InvalidConfiguration);
+        }
+
+        // Discard a pending sample, so that only the triggered one is returned
+        if self.mag_data_ready_get().await? == 1 {
+            self.magnetic_raw_get().await?;
+        }
+
+        let saved = self.read_reg::<CfgRegB>().await?;
+        let mut reg = saved;
+        if off_canc == 1 {
+            reg.set_set_rst(SetRst::SensOffCancEveryOdr as u8);
+        }
+        reg.set_off_canc_one_shot(off_canc);
+        self.write_reg(&reg).await?;
+
+        let result = self.single_measurement().await;
+        // Restore CFG_REG_B on both paths, reporting the measurement error first
+        if reg.into_bits() != saved.into_bits() {
+            let restored = self.write_reg(&saved).await;
+            let raw = result?;
+            restored?;
+            return Ok(raw);
+        }
+        result
+    }
+
+    async fn single_measurement(&mut self) -> Result<[i16; 3], Error<B::Error>> {
+        self.operating_mode_set(Md::SingleTrigger).await?;
+        self.mag_data_ready_wait(SINGLE_MEASUREMENT_TIMEOUT_MS)
+            .await?;
+        self.magnetic_raw_get().await
+    }
+
+    /// Initializes the sensor and applies the requested configuration.
+    ///
+    /// This function performs the complete start-up sequence:
+    /// 1. validates the configuration, before accessing the device;
+    /// 2. checks the `WHO_AM_I` register against [`IIS2MDC_ID`];
+    /// 3. performs a software reset and polls the `soft_rst` bit until it is cleared;
+    /// 4. reboots the memory content and polls the `reboot` bit until it is cleared;
+    /// 5. writes the configuration with `apply_config`;
+    /// 6. waits for the power-up time needed to get stable output.
+    ///
+    /// Each polling loop is bounded, so a missing or unresponsive device does not hang the caller.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired sensor configuration, represented by the `Config` struct.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the sensor is successfully initialized. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidDeviceId(u8)`: This error is returned if the device ID does not match.
+    /// - `Error::Timeout`: This error is returned if the reset or the reboot does not complete.
+    /// - `Error::InvalidConfiguration`: This error is returned if the configuration is not valid.
+    pub async fn init(&mut self, val: &Config) -> Result<(), Error<B::Error>> {
+        val.validate()?;
+
+        let id = self.device_id_get().await?;
+        if id != IIS2MDC_ID {
+            return Err(Error::InvalidDeviceId(id));
+        }
+
+        // Restore default configuration
+        self.reset_set(1).await?;
+        let mut retries = 0;
+        while self.reset_get().await? != 0 {
+            retries += 1;
+            if retries > INIT_MAX_RETRIES {
+                return Err(Error::Timeout);
+            }
+            self.tim.delay_ms(INIT_POLL_DELAY_MS).await;
+        }
+
+        // Reload calibration parameters
+        self.boot_set(1).await?;
+        self.tim.delay_ms(BOOT_TIME_MS).await;
+        let mut retries = 0;
+        while self.boot_get().await? != 0 {
+            retries += 1;
+            if retries > INIT_MAX_RETRIES {
+                return Err(Error::Timeout);
+            }
+            self.tim.delay_ms(INIT_POLL_DELAY_MS).await;
+        }
+
+        self.apply_config(val).await?;
+        // Power up and wait for stable output
+        self.tim.delay_ms(POWER_UP_TIME_MS).await;
+
+        Ok(())
+    }
+
+    /// Runs the self-test procedure.
+    ///
+    /// This function configures the sensor in continuous mode at 100 Hz with offset cancellation,
+    /// temperature compensation and block data update, then averages [`SELF_TEST_SAMPLES`] samples
+    /// with the self-test disabled and [`SELF_TEST_SAMPLES`] samples with the self-test enabled.
+    /// The absolute difference on each axis must be within [`SELF_TEST_MIN_MG`](crate::SELF_TEST_MIN_MG) and
+    /// [`SELF_TEST_MAX_MG`](crate::SELF_TEST_MAX_MG) milligauss.
+    ///
+    /// The configuration found before the test is restored afterwards, with the self-test disabled.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SelfTestReport, Error<B::Error>>`: Returns the per-axis report of the self-test. Use
+    ///   `SelfTestReport::check` to turn a failing report into `Error::SelfTestFailed`. Returns an
+    ///   `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the current configuration cannot be decoded.
+    /// - `Error::Timeout`: This error is returned if the sensor stops producing data.
+    pub async fn self_test(&mut self) -> Result<SelfTestReport, Error<B::Error>> {
+        let previous = self.read_config().await?;
+        let config = Config {
+            md: Md::ContinuousMode,
+            odr: Odr::_100hz,
+            lp: Lp::HighResolution,
+            comp_temp_en: 1,
+            set_rst: SetRst::SensOffCancEveryOdr,
+            off_canc_one_shot: 0,
+            bdu: 1,
+            ..previous
+        };
+
+        let result = self.self_test_run(&config).await;
+
+        // Restore the previous configuration, self-test disabled
+        self.apply_config(&previous).await?;
+        result
+    }
+
+    async fn self_test_run(&mut self, config: &Config) -> Result<SelfTestReport, Error<B::Error>> {
+        self.apply_config(config).await?;
+        // Power up and wait for stable output
+        self.tim.delay_ms(POWER_UP_TIME_MS).await;
+        let baseline = self.self_test_average().await?;
+
+        // Enable self test mode
+        self.self_test_set(1).await?;
+        self.tim.delay_ms(SELF_TEST_SETTLING_MS).await;
+        let self_test = self.self_test_average().await?;
+
+        Ok(SelfTestReport::new(baseline, self_test))
+    }
+
+    async fn self_test_average(&mut self) -> Result<[f32; 3], Error<B::Error>> {
+        // Flush old samples
+        if self.mag_data_ready_get().await? == 1 {
+            self.magnetic_raw_get().await?;
+        }
+
+        let mut sum = [0.0_f32; 3];
+        for _ in 0..SELF_TEST_SAMPLES {
+            self.mag_data_ready_wait(SELF_TEST_DRDY_TIMEOUT_MS).await?;
+            let field = self.magnetic_get().await?.to_array();
+            (0..3).for_each(|axis| sum[axis] += field[axis]);
+        }
+
+        Ok(sum.map(|v| v / SELF_TEST_SAMPLES as f32))
+    }
+
+    /// Arms a wake-on-magnet low-power preset.
+    ///
+    /// This function measures the ambient field and programs it as hard-iron offset, so that the
+    /// threshold interrupt, checked after hard-iron correction, fires when the field deviates from
+    /// this baseline by more than `threshold_mg` on any axis. The sequence is:
+    ///
+    /// 1. disable the interrupt generator and clear the hard-iron offset;
+    /// 2. apply the preset configuration (see `WakeOnMagnet::to_config`) and wait for stable output;
+    /// 3. average [`WAKE_AMBIENT_SAMPLES`] samples and write them with `mag_user_offset_set`;
+    /// 4. enable the latched, active-high threshold interrupt and clear any pending event.
+    ///
+    /// While the preset is armed, the output registers report the deviation from the baseline.
+    ///
+    /// # Arguments
+    ///
+    /// * `preset`: The low-power preset, represented by the `WakeOnMagnet` enum.
+    /// * `threshold_mg`: The allowed deviation from the ambient field on each axis, in milligauss.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<[i16; 3], Error<B::Error>>`: Returns the ambient field written as offset, in LSB (1 LSB = 1.5 mG). Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidConfiguration`: This error is returned if the threshold is not valid.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the current configuration cannot be decoded.
+    /// - `Error::Timeout`: This error is returned if no sample is produced while measuring the ambient field.
+    pub async fn wake_on_magnet_set(
+        &mut self,
+        preset: WakeOnMagnet,
+        threshold_mg: f32,
+    ) -> Result<[i16; 3], Error<B::Error>> {
+        let threshold = preset.threshold_config(threshold_mg);
+        threshold.validate()?;
+        let config = preset.to_config(&self.read_config().await?);
+
+        self.threshold_event_set(&ThresholdConfig {
+            ien: 0,
+            ..threshold
+        })
+        .await?;
+        self.mag_user_offset_set(&[0; 3]).await?;
+        self.apply_config(&config).await?;
+        // Power up and wait for stable output
+        self.tim.delay_ms(POWER_UP_TIME_MS).await;
+
+        let ambient = self.wake_on_magnet_ambient(preset).await?;
+        self.mag_user_offset_set(&ambient).await?;
+
+        self.threshold_event_set(&threshold).await?;
+        // Clear an event latched before the baseline was in place
+        self.int_gen_source_get().await?;
+
+        Ok(ambient)
+    }
+
+    async fn wake_on_magnet_ambient(
+        &mut self,
+        preset: WakeOnMagnet,
+    ) -> Result<[i16; 3], Error<B::Error>> {
+        // Flush old samples
+        if self.mag_data_ready_get().await? == 1 {
+            self.magnetic_raw_get().await?;
+        }
+
+        let mut sum = [0_i32; 3];
+        for _ in 0..WAKE_AMBIENT_SAMPLES {
+            self.mag_data_ready_wait(preset.drdy_timeout_ms()).await?;
+            let raw = self.magnetic_raw_get().await?;
+            (0..3).for_each(|axis| sum[axis] += raw[axis] as i32);
+        }
+
+        Ok(sum.map(|v| libm::roundf(v as f32 / WAKE_AMBIENT_SAMPLES as f32) as i16))
+    }
+
+    /// Writes the whole sensor configuration.
+    ///
+    /// This function encodes the configuration into the `CFG_REG_A`, `CFG_REG_B` and `CFG_REG_C`
+    /// registers and writes them in a single bus transaction. The `soft_rst`, `reboot` and
+    /// `self_test` bits are written as `0`.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired sensor configuration, represented by the `Config` struct.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the configuration is successfully written. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidConfiguration`: This error is returned if the configuration is not valid.
+    pub async fn apply_config(&mut self, val: &Config) -> Result<(), Error<B::Error>> {
+        val.validate()?;
+        let (reg_a, reg_b, reg_c) = val.to_registers();
+        self.write_to_register(
+            Reg::CfgRegA as u8,
+            &[reg_a.into_bits(), reg_b.into_bits(), reg_c.into_bits()],
+        )
+        .await
+    }
+
+    /// Reads back the whole sensor configuration.
+    ///
+    /// This function reads the `CFG_REG_A`, `CFG_REG_B` and `CFG_REG_C` registers in a single bus
+    /// transaction and decodes them into a `Config` struct.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Config, Error<B::Error>>`: Returns the current sensor configuration. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if a register holds a value that cannot be decoded.
+    pub async fn read_config(&mut self) -> Result<Config, Error<B::Error>> {
+        let mut buff = [0u8; 3];
+        self.read_from_register(Reg::CfgRegA as u8, &mut buff)
+            .await?;
+
+        Config::from_registers(
+            CfgRegA::from_bits(buff[0]),
+            CfgRegB::from_bits(buff[1]),
+            CfgRegC::from_bits(buff[2]),
+        )
+    }
+
+    /// Waits for a new magnetic sample on the INT/DRDY pin and retrieves the raw output values.
+    ///
+    /// This function enables the data-ready signal on the INT/DRDY pin (`drdy_on_pin` field of the
+    /// `CFG_REG_C` register) if it is not already enabled, then waits for the pin to go high
+    /// instead of polling the `STATUS_REG` register. The output registers are read as soon as the
+    /// pin is asserted, which also clears the data-ready signal.
+    ///
+    /// # Arguments
+    ///
+    /// * `drdy`: The GPIO connected to the INT/DRDY pin of the sensor.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<[i16; 3], Error<B::Error>>`: Returns an array containing the raw magnetic output values. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::Pin(ErrorKind)`: This error is returned if waiting on the GPIO fails.
+    pub async fn magnetic_raw_wait<W: Wait>(
+        &mut self,
+        drdy: &mut W,
+    ) -> Result<[i16; 3], Error<B::Error>> {
+        let mut reg = self.read_reg::<CfgRegC>().await?;
+        if reg.drdy_on_pin() == 0 {
+            reg.set_drdy_on_pin(1);
+            self.write_reg(&reg).await?;
+        }
+
+        drdy.wait_for_high()
+            .await
+            .map_err(|e| Error::Pin(e.kind()))?;
+
+        self.magnetic_raw_get().await
+    }
+
+    /// Waits for a threshold interrupt on the INT/DRDY pin and retrieves the decoded event.
+    ///
+    /// This function enables the interrupt generator (`ien` field of the `INT_CTRL_REG` register)
+    /// and the interrupt signal on the INT/DRDY pin (`int_on_pin` field of the `CFG_REG_C`
+    /// register), then waits for the pin to signal an interrupt with the configured polarity
+    /// (`iea`). The `INT_SOURCE_REG` register is then read, which also clears a latched interrupt
+    /// (`iel`).
+    ///
+    /// A latched interrupt keeps the pin asserted until it is cleared, so the pin level is awaited:
+    /// an interrupt that latched before or while waiting is returned without missing its edge. A
+    /// pulsed interrupt is awaited on its edge, a rising edge when active high, a falling edge when
+    /// active low. Signals not caused by the threshold generator, e.g. when `drdy_on_pin` is also
+    /// enabled, are ignored; with a latched interrupt, the data-ready signal keeps the pin asserted
+    /// and should not be routed to it. The threshold and the enabled axes must be configured
+    /// beforehand, e.g. with `threshold_event_set`.
+    ///
+    /// # Arguments
+    ///
+    /// * `int`: The GPIO connected to the INT/DRDY pin of the sensor.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ThresholdEvent, Error<B::Error>>`: Returns the decoded event. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::Pin(ErrorKind)`: This error is returned if waiting on the GPIO fails.
+    pub async fn threshold_event_wait<W: Wait>(
+        &mut self,
+        int: &mut W,
+    ) -> Result<ThresholdEvent, Error<B::Error>> {
+        let mut ctrl = self.read_reg::<IntCtrlReg>().await?;
+        if ctrl.ien() == 0 {
+            ctrl.set_ien(1);
+            self.write_reg(&ctrl).await?;
+        }
+
+        let mut reg = self.read_reg::<CfgRegC>().await?;
+        if reg.int_on_pin() == 0 {
+            reg.set_int_on_pin(1);
+            self.write_reg(&reg).await?;
+        }
+
+        let latched = ctrl.iel() == Iel::Latched as u8;
+        let active_high = ctrl.iea() == Iea::ActiveHigh as u8;
+        loop {
+            match (latched, active_high) {
+                (true, true) => int.wait_for_high().await,
+                (true, false) => int.wait_for_low().await,
+                (false, true) => int.wait_for_rising_edge().await,
+                (false, false) => int.wait_for_falling_edge().await,
+            }
+            .map_err(|e| Error::Pin(e.kind()))?;
+
+            let event = self.threshold_event_source_get().await?;
+            if event.int {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Reads every register of the device.
+    ///
+    /// This function burst-reads the hard-iron offset, `WHO_AM_I`, and the registers from
+    /// `CFG_REG_A` to `TEMP_OUT_H_REG`, in three bus transactions, and decodes them into a
+    /// `RegisterSnapshot`. Reading the registers has the usual side effects: a latched interrupt is
+    /// cleared by the read of `INT_SOURCE_REG`, and the data-ready flags by the read of the outputs.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RegisterSnapshot, Error<B::Error>>`: Returns the content of the registers. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn dump_registers(&mut self) -> Result<RegisterSnapshot, Error<B::Error>> {
+        let mut offset = [0u8; 6];
+        self.read_from_register(Reg::OffsetXRegL as u8, &mut offset)
+            .await?;
+        let who_am_i = self.device_id_get().await?;
+        let mut block = [0u8; SNAPSHOT_BLOCK_LEN];
+        self.read_from_register(Reg::CfgRegA as u8, &mut block)
+            .await?;
+
+        Ok(RegisterSnapshot::from_registers(offset, who_am_i, block))
+    }
+
+    /// Writes back the writable registers of a snapshot.
+    ///
+    /// This function writes the hard-iron offset, the interrupt threshold, the registers from
+    /// `CFG_REG_B` to `INT_CTRL_REG`, then `CFG_REG_A` in a separate final write, so that the
+    /// operating mode is restored last. The `soft_rst` and `reboot` commands are not restored.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The snapshot returned by `dump_registers`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the registers are successfully written. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub async fn restore_registers(
+        &mut self,
+        val: &RegisterSnapshot,
+    ) -> Result<(), Error<B::Error>> {
+        let (offset, config, threshold) = val.to_registers();
+        self.write_to_register(Reg::OffsetXRegL as u8, &offset)
+            .await?;
+        self.write_to_register(Reg::IntThsLReg as u8, &threshold)
+            .await?;
+        self.write_to_register(Reg::CfgRegB as u8, &config[1..])
+            .await?;
+        self.write_to_register(Reg::CfgRegA as u8, &config[..1])
+            .await
+    }
+}
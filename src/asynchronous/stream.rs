@@ -0,0 +1,43 @@
+use super::{BusOperation, Iis2mdc};
+use crate::stream::SAMPLE_POLLS_PER_PERIOD;
+use crate::{Error, Sample};
+use embedded_hal_async::delay::DelayNs;
+
+/// Asynchronous stream of the samples of the sensor, created with `samples`.
+///
+/// This is the asynchronous counterpart of [`crate::SampleIter`]: each call to
+/// [`SampleStream::next`] waits for new data, paced to the output data rate. The stream never
+/// ends: an error is yielded as `Some(Err(..))`.
+pub struct SampleStream<'a, B, T> {
+    pub(crate) sensor: &'a mut Iis2mdc<B, T>,
+    pub(crate) period_ms: u32,
+    pub(crate) temperature: bool,
+}
+
+impl<B: BusOperation, T: DelayNs> SampleStream<'_, B, T> {
+    /// Waits for the next sample.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Result<Sample, Error<B::Error>>>`: Returns the next sample, never `None`.
+    pub async fn next(&mut self) -> Option<Result<Sample, Error<B::Error>>> {
+        Some(self.read().await)
+    }
+
+    async fn read(&mut self) -> Result<Sample, Error<B::Error>> {
+        let poll_ms = (self.period_ms / SAMPLE_POLLS_PER_PERIOD).max(1);
+        let timeout_ms = 2 * self.period_ms;
+
+        let mut elapsed = 0;
+        while self.sensor.mag_data_ready_get().await? == 0 {
+            if elapsed >= timeout_ms {
+                return Err(Error::Timeout);
+            }
+            self.sensor.tim.delay_ms(poll_ms).await;
+            elapsed += poll_ms;
+        }
+
+        let raw = self.sensor.sample_raw_get().await?;
+        Ok(Sample::from_raw(&raw, self.temperature))
+    }
+}
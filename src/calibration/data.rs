@@ -0,0 +1,206 @@
+use super::SoftIronFit;
+use crate::IIS2MDC_ID;
+
+/// Version of the binary layout written by [`CalibrationData::serialize`].
+pub const CALIBRATION_DATA_VERSION: u8 = 2;
+
+/// Size in bytes of a serialized [`CalibrationData`].
+pub const CALIBRATION_DATA_SIZE: usize = 64;
+
+const HARD_IRON_POS: usize = 2;
+const SOFT_IRON_POS: usize = HARD_IRON_POS + 3 * 2;
+const SOFT_IRON_OFFSET_POS: usize = SOFT_IRON_POS + 9 * 4;
+const TEMPERATURE_POS: usize = SOFT_IRON_OFFSET_POS + 3 * 4;
+const CRC_POS: usize = TEMPERATURE_POS + 4;
+
+/// Errors returned when serializing or deserializing calibration data.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum CalibrationDataError {
+    /// The buffer is shorter than [`CALIBRATION_DATA_SIZE`].
+    BufferTooSmall,
+    /// The data was written with an unknown layout version.
+    UnsupportedVersion(u8),
+    /// The stored CRC does not match the content, e.g. because the storage was erased or corrupted.
+    CrcMismatch,
+}
+
+impl core::fmt::Display for CalibrationDataError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CalibrationDataError::BufferTooSmall => write!(f, "Buffer too small"),
+            CalibrationDataError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported calibration data version: {version}")
+            }
+            CalibrationDataError::CrcMismatch => write!(f, "Calibration data CRC mismatch"),
+        }
+    }
+}
+
+impl core::error::Error for CalibrationDataError {}
+
+/// Calibration data to be stored in non-volatile memory.
+///
+/// The binary layout, in little-endian byte order, is:
+///
+/// | Offset | Size | Content                                  |
+/// |--------|------|------------------------------------------|
+/// | 0      | 1    | Layout version                           |
+/// | 1      | 1    | Sensor ID (`WHO_AM_I`)                   |
+/// | 2      | 6    | Hard-iron offset X, Y, Z (`i16`)         |
+/// | 8      | 36   | Soft-iron matrix, row-major (`f32`)      |
+/// | 44     | 12   | Soft-iron offset X, Y, Z (`f32`)         |
+/// | 56     | 4    | Temperature at calibration time (`f32`)  |
+/// | 60     | 4    | CRC-32 (IEEE) of the previous 60 bytes   |
+///
+/// The soft-iron correction is relative to the raw data the samples were taken from, i.e. with the
+/// stored hard-iron offset programmed in the sensor. Restore both together and apply the
+/// correction with [`CalibrationData::soft_iron_fit`].
+///
+/// The sensor ID is the `WHO_AM_I` value, which is the same for every IIS2MDC: it identifies the
+/// part type the calibration was taken on, not the individual unit.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CalibrationData {
+    /// ID of the part type the calibration belongs to, as read from `WHO_AM_I`.
+    pub sensor_id: u8,
+    /// Hard-iron offset for the `OFFSET_X/Y/Z_REG` registers, in LSB (1 LSB = 1.5 mG).
+    pub hard_iron: [i16; 3],
+    /// Soft-iron correction matrix, in row-major order. See `SoftIronFit::matrix`.
+    pub soft_iron: [[f32; 3]; 3],
+    /// Center of the soft-iron ellipsoid, in LSB. See `SoftIronFit::offset`.
+    pub soft_iron_offset: [f32; 3],
+    /// Temperature at calibration time, in degrees Celsius.
+    pub temperature: f32,
+}
+
+impl Default for CalibrationData {
+    fn default() -> Self {
+        Self {
+            sensor_id: IIS2MDC_ID,
+            hard_iron: [0; 3],
+            soft_iron: [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            soft_iron_offset: [0.0; 3],
+            temperature: 0.0,
+        }
+    }
+}
+
+impl CalibrationData {
+    /// Returns the stored soft-iron correction, ready for `SoftIronFit::apply`.
+    ///
+    /// Only the offset and matrix are stored: the fit metrics are zero.
+    pub fn soft_iron_fit(&self) -> SoftIronFit {
+        SoftIronFit {
+            offset: self.soft_iron_offset,
+            matrix: self.soft_iron,
+            ..Default::default()
+        }
+    }
+
+    /// Writes the calibration data to a buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf`: The destination buffer, at least [`CALIBRATION_DATA_SIZE`] bytes long.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<usize, CalibrationDataError>`: Returns the number of bytes written.
+    ///
+    /// # Errors
+    ///
+    /// - `CalibrationDataError::BufferTooSmall`: This error is returned if the buffer is too short.
+    pub fn serialize(&self, buf: &mut [u8]) -> Result<usize, CalibrationDataError> {
+        let buf = buf
+            .get_mut(..CALIBRATION_DATA_SIZE)
+            .ok_or(CalibrationDataError::BufferTooSmall)?;
+
+        buf[0] = CALIBRATION_DATA_VERSION;
+        buf[1] = self.sensor_id;
+        for (i, val) in self.hard_iron.iter().enumerate() {
+            let pos = HARD_IRON_POS + 2 * i;
+            buf[pos..pos + 2].copy_from_slice(&val.to_le_bytes());
+        }
+        for (i, val) in self.soft_iron.as_flattened().iter().enumerate() {
+            let pos = SOFT_IRON_POS + 4 * i;
+            buf[pos..pos + 4].copy_from_slice(&val.to_le_bytes());
+        }
+        for (i, val) in self.soft_iron_offset.iter().enumerate() {
+            let pos = SOFT_IRON_OFFSET_POS + 4 * i;
+            buf[pos..pos + 4].copy_from_slice(&val.to_le_bytes());
+        }
+        buf[TEMPERATURE_POS..CRC_POS].copy_from_slice(&self.temperature.to_le_bytes());
+        let crc = crc32(&buf[..CRC_POS]);
+        buf[CRC_POS..].copy_from_slice(&crc.to_le_bytes());
+
+        Ok(CALIBRATION_DATA_SIZE)
+    }
+
+    /// Reads calibration data from a buffer written by [`CalibrationData::serialize`].
+    ///
+    /// # Arguments
+    ///
+    /// * `buf`: The source buffer, at least [`CALIBRATION_DATA_SIZE`] bytes long.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<CalibrationData, CalibrationDataError>`: Returns the decoded calibration data.
+    ///
+    /// # Errors
+    ///
+    /// - `CalibrationDataError::BufferTooSmall`: This error is returned if the buffer is too short.
+    /// - `CalibrationDataError::UnsupportedVersion(u8)`: This error is returned if the layout version is unknown.
+    /// - `CalibrationDataError::CrcMismatch`: This error is returned if the content does not match the CRC.
+    pub fn deserialize(buf: &[u8]) -> Result<Self, CalibrationDataError> {
+        let buf = buf
+            .get(..CALIBRATION_DATA_SIZE)
+            .ok_or(CalibrationDataError::BufferTooSmall)?;
+
+        // The version comes first, since another layout may differ in size and CRC coverage
+        if buf[0] != CALIBRATION_DATA_VERSION {
+            return Err(CalibrationDataError::UnsupportedVersion(buf[0]));
+        }
+        let crc = u32::from_le_bytes([
+            buf[CRC_POS],
+            buf[CRC_POS + 1],
+            buf[CRC_POS + 2],
+            buf[CRC_POS + 3],
+        ]);
+        if crc != crc32(&buf[..CRC_POS]) {
+            return Err(CalibrationDataError::CrcMismatch);
+        }
+
+        let read_f32 =
+            |pos: usize| f32::from_le_bytes([buf[pos], buf[pos + 1], buf[pos + 2], buf[pos + 3]]);
+        let hard_iron = [0, 1, 2].map(|i| {
+            let pos = HARD_IRON_POS + 2 * i;
+            i16::from_le_bytes([buf[pos], buf[pos + 1]])
+        });
+        let soft_iron =
+            [0, 1, 2].map(|row| [0, 1, 2].map(|col| read_f32(SOFT_IRON_POS + 4 * (3 * row + col))));
+
+        Ok(Self {
+            sensor_id: buf[1],
+            hard_iron,
+            soft_iron,
+            soft_iron_offset: [0, 1, 2].map(|i| read_f32(SOFT_IRON_OFFSET_POS + 4 * i)),
+            temperature: read_f32(TEMPERATURE_POS),
+        })
+    }
+}
+
+/// CRC-32 (IEEE 802.3, reflected, polynomial 0xEDB88320), computed bitwise to avoid a lookup table.
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFF_u32;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
@@ -0,0 +1,148 @@
+use super::solve;
+
+/// Minimum number of samples required before a fit is attempted.
+pub const HARD_IRON_MIN_SAMPLES: u32 = 4;
+
+/// Algorithm used to estimate the center of the sphere described by the samples.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum HardIronMethod {
+    /// Center of the bounding box of the samples. Fast, but sensitive to outliers and requires a
+    /// full rotation on every axis.
+    MinMax,
+    /// Least-squares sphere fit. Robust to noise and works with partial rotations.
+    #[default]
+    LeastSquares,
+}
+
+/// Result of a hard-iron fit.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HardIronFit {
+    /// Estimated hard-iron bias on the X, Y, Z axes, in LSB (1 LSB = 1.5 mG). This is the format
+    /// expected by the `OFFSET_X/Y/Z_REG` registers.
+    pub offset: [i16; 3],
+    /// Estimated radius of the sphere, i.e. the magnitude of the local field, in LSB.
+    pub radius: f32,
+}
+
+/// Hard-iron calibrator.
+///
+/// Feed raw samples from `magnetic_raw_get` with [`HardIronCalibrator::add_sample`] while the
+/// device is rotated in every direction, then call [`HardIronCalibrator::fit`] to estimate the
+/// center of the sphere described by the samples.
+///
+/// The fitted offset is relative to the data the samples were taken from: if an offset is already
+/// programmed in the `OFFSET_X/Y/Z_REG` registers, the new offset must be added to it, as done by
+/// `hard_iron_offset_apply`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct HardIronCalibrator {
+    count: u32,
+    min: [i16; 3],
+    max: [i16; 3],
+    // Normal equations of the linear sphere model, see `fit_least_squares`.
+    ata: [[f64; 4]; 4],
+    atb: [f64; 4],
+}
+
+impl Default for HardIronCalibrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HardIronCalibrator {
+    /// Creates an empty calibrator.
+    pub const fn new() -> Self {
+        Self {
+            count: 0,
+            min: [i16::MAX; 3],
+            max: [i16::MIN; 3],
+            ata: [[0.0; 4]; 4],
+            atb: [0.0; 4],
+        }
+    }
+
+    /// Clears all the collected samples.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns the number of samples collected so far.
+    pub fn sample_count(&self) -> u32 {
+        self.count
+    }
+
+    /// Adds a raw X, Y, Z sample, as returned by `magnetic_raw_get`.
+    ///
+    /// Samples are accumulated rather than stored, so there is no limit on their number.
+    pub fn add_sample(&mut self, raw: [i16; 3]) {
+        for (axis, &val) in raw.iter().enumerate() {
+            self.min[axis] = self.min[axis].min(val);
+            self.max[axis] = self.max[axis].max(val);
+        }
+
+        let [x, y, z] = raw.map(f64::from);
+        let row = [x, y, z, 1.0];
+        let rhs = x * x + y * y + z * z;
+        for i in 0..4 {
+            for j in 0..4 {
+                self.ata[i][j] += row[i] * row[j];
+            }
+            self.atb[i] += row[i] * rhs;
+        }
+
+        self.count += 1;
+    }
+
+    /// Estimates the hard-iron bias with the requested method.
+    ///
+    /// Returns `None` if fewer than [`HARD_IRON_MIN_SAMPLES`] samples were collected, or if the
+    /// samples do not cover enough directions to fit a sphere.
+    pub fn fit(&self, method: HardIronMethod) -> Option<HardIronFit> {
+        if self.count < HARD_IRON_MIN_SAMPLES {
+            return None;
+        }
+
+        match method {
+            HardIronMethod::MinMax => self.fit_min_max(),
+            HardIronMethod::LeastSquares => self.fit_least_squares(),
+        }
+    }
+
+    fn fit_min_max(&self) -> Option<HardIronFit> {
+        let mut center = [0.0_f64; 3];
+        let mut radius = 0.0_f64;
+        for (axis, c) in center.iter_mut().enumerate() {
+            let (min, max) = (f64::from(self.min[axis]), f64::from(self.max[axis]));
+            *c = (max + min) / 2.0;
+            radius += (max - min) / 6.0;
+        }
+
+        Some(HardIronFit {
+            offset: center.map(to_lsb),
+            radius: radius as f32,
+        })
+    }
+
+    fn fit_least_squares(&self) -> Option<HardIronFit> {
+        // x^2 + y^2 + z^2 = 2*cx*x + 2*cy*y + 2*cz*z + (r^2 - cx^2 - cy^2 - cz^2)
+        let p = solve(self.ata, self.atb)?;
+        let center = [p[0] / 2.0, p[1] / 2.0, p[2] / 2.0];
+        let radius2 = p[3] + center.iter().map(|c| c * c).sum::<f64>();
+        if radius2 <= 0.0 {
+            return None;
+        }
+
+        Some(HardIronFit {
+            offset: center.map(to_lsb),
+            radius: libm::sqrt(radius2) as f32,
+        })
+    }
+}
+
+fn to_lsb(val: f64) -> i16 {
+    libm::round(val).clamp(f64::from(i16::MIN), f64::from(i16::MAX)) as i16
+}
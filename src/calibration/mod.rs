@@ -0,0 +1,114 @@
+//! Magnetometer calibration helpers.
+//!
+//! The calibrators are `no_std`, allocation-free and consume samples one at a time, so they can
+//! run directly on the target while the device is rotated.
+
+pub mod data;
+pub mod hard_iron;
+pub mod soft_iron;
+
+pub use data::{
+    CALIBRATION_DATA_SIZE, CALIBRATION_DATA_VERSION, CalibrationData, CalibrationDataError,
+};
+pub use hard_iron::{HARD_IRON_MIN_SAMPLES, HardIronCalibrator, HardIronFit, HardIronMethod};
+pub use soft_iron::{SOFT_IRON_MIN_SAMPLES, SoftIronCalibrator, SoftIronFit};
+
+/// Solves the linear system `a * x = b` with Gaussian elimination and partial pivoting.
+///
+/// Returns `None` if the system is singular, e.g. when the collected samples do not span enough
+/// directions.
+pub(crate) fn solve<const N: usize>(mut a: [[f64; N]; N], mut b: [f64; N]) -> Option<[f64; N]> {
+    // Relative to the largest entry, since the rounding left in a zero pivot scales with it
+    let scale = a.iter().flatten().fold(0.0_f64, |max, v| max.max(v.abs()));
+    let tolerance = scale * N as f64 * f64::EPSILON;
+    for col in 0..N {
+        let pivot = (col..N).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() <= tolerance {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+
+        for row in col + 1..N {
+            let factor = a[row][col] / a[col][col];
+            let pivot_row = a[col];
+            for (dst, src) in a[row][col..].iter_mut().zip(&pivot_row[col..]) {
+                *dst -= factor * src;
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+
+    let mut x = [0.0; N];
+    for row in (0..N).rev() {
+        let sum: f64 = (row + 1..N).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+
+    Some(x)
+}
+
+/// Computes the eigenvalues and eigenvectors of a symmetric 3x3 matrix with the cyclic Jacobi
+/// method.
+///
+/// Returns the eigenvalues and a matrix whose columns are the matching unit eigenvectors.
+pub(crate) fn symmetric_eigen(mut a: [[f64; 3]; 3]) -> ([f64; 3], [[f64; 3]; 3]) {
+    const MAX_SWEEPS: usize = 32;
+
+    let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+    for _ in 0..MAX_SWEEPS {
+        let off = a[0][1] * a[0][1] + a[0][2] * a[0][2] + a[1][2] * a[1][2];
+        if off < f64::EPSILON * f64::EPSILON {
+            break;
+        }
+
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q] == 0.0 {
+                continue;
+            }
+
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (theta.abs() + libm::sqrt(theta * theta + 1.0));
+            let c = 1.0 / libm::sqrt(t * t + 1.0);
+            let s = t * c;
+
+            for row in a.iter_mut() {
+                let (akp, akq) = (row[p], row[q]);
+                row[p] = c * akp - s * akq;
+                row[q] = s * akp + c * akq;
+            }
+            let (rp, rq) = (a[p], a[q]);
+            a[p] = [0, 1, 2].map(|k| c * rp[k] - s * rq[k]);
+            a[q] = [0, 1, 2].map(|k| s * rp[k] + c * rq[k]);
+            for row in v.iter_mut() {
+                let (vp, vq) = (row[p], row[q]);
+                row[p] = c * vp - s * vq;
+                row[q] = s * vp + c * vq;
+            }
+        }
+    }
+
+    ([a[0][0], a[1][1], a[2][2]], v)
+}
+
+/// Multiplies two 3x3 matrices.
+pub(crate) fn mat_mul(a: &[[f64; 3]; 3], b: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, val) in row.iter_mut().enumerate() {
+            *val = (0..3).map(|k| a[i][k] * b[k][j]).sum();
+        }
+    }
+    out
+}
+
+/// Returns the transpose of a 3x3 matrix.
+pub(crate) fn transpose(a: &[[f64; 3]; 3]) -> [[f64; 3]; 3] {
+    let mut out = [[0.0; 3]; 3];
+    for (i, row) in out.iter_mut().enumerate() {
+        for (j, val) in row.iter_mut().enumerate() {
+            *val = a[j][i];
+        }
+    }
+    out
+}
@@ -0,0 +1,220 @@
+use super::{mat_mul, solve, symmetric_eigen, transpose};
+use crate::measurement::MagneticField;
+
+/// Minimum number of samples required before a fit is attempted.
+pub const SOFT_IRON_MIN_SAMPLES: u32 = 9;
+
+// Raw samples are scaled down before being accumulated to keep the normal equations well
+// conditioned. The fitted correction matrix does not depend on this scale.
+const SAMPLE_SCALE: f64 = 1.0 / 1024.0;
+
+/// Result of a soft-iron fit.
+///
+/// The corrected field is `matrix * (raw - offset)`, which maps the ellipsoid described by the
+/// samples back onto a sphere of the fitted `radius`.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SoftIronFit {
+    /// Center of the ellipsoid on the X, Y, Z axes, in LSB (1 LSB = 1.5 mG).
+    pub offset: [f32; 3],
+    /// Symmetric soft-iron correction matrix, in row-major order.
+    pub matrix: [[f32; 3]; 3],
+    /// Radius of the corrected sphere, i.e. the magnitude of the local field, in LSB.
+    pub radius: f32,
+    /// RMS deviation of the corrected magnitude from `radius`, relative to `radius`.
+    ///
+    /// Values above a few percent usually mean noisy samples or a disturbed environment.
+    pub residual: f32,
+    /// Spread of the corrected samples over the sphere, from 0.0 to 1.0.
+    ///
+    /// This is the smallest spread along any direction, normalized so that samples evenly
+    /// distributed over the whole sphere give 1.0. Rotating the device around a single axis
+    /// gives a value close to 0.0.
+    pub coverage: f32,
+}
+
+impl SoftIronFit {
+    /// Applies the correction to a raw sample.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw`: The raw X, Y, Z values, as returned by `magnetic_raw_get`.
+    ///
+    /// # Returns
+    ///
+    /// * `MagneticField`: The corrected field, in milligauss.
+    pub fn apply(&self, raw: [i16; 3]) -> MagneticField {
+        let centered = [0, 1, 2].map(|axis| f32::from(raw[axis]) - self.offset[axis]);
+        let [x, y, z] = self
+            .matrix
+            .map(|row| (row[0] * centered[0] + row[1] * centered[1] + row[2] * centered[2]) * 1.5);
+
+        MagneticField { x, y, z }
+    }
+}
+
+/// Soft-iron calibrator.
+///
+/// Fits a general ellipsoid to raw samples from `magnetic_raw_get` collected while the device is
+/// rotated in every direction. Unlike the hard-iron offset, which the sensor subtracts in hardware,
+/// the soft-iron correction must be applied in software with [`SoftIronFit::apply`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SoftIronCalibrator {
+    count: u32,
+    // Normal equations of the linear ellipsoid model, see `fit`.
+    ata: [[f64; 9]; 9],
+    atb: [f64; 9],
+    // First and second moments of the samples, used for the coverage metric.
+    sum: [f64; 3],
+    sum_sq: [[f64; 3]; 3],
+}
+
+impl Default for SoftIronCalibrator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SoftIronCalibrator {
+    /// Creates an empty calibrator.
+    pub const fn new() -> Self {
+        Self {
+            count: 0,
+            ata: [[0.0; 9]; 9],
+            atb: [0.0; 9],
+            sum: [0.0; 3],
+            sum_sq: [[0.0; 3]; 3],
+        }
+    }
+
+    /// Clears all the collected samples.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Returns the number of samples collected so far.
+    pub fn sample_count(&self) -> u32 {
+        self.count
+    }
+
+    /// Adds a raw X, Y, Z sample, as returned by `magnetic_raw_get`.
+    pub fn add_sample(&mut self, raw: [i16; 3]) {
+        let s = raw.map(|v| f64::from(v) * SAMPLE_SCALE);
+        let [x, y, z] = s;
+        let row = [
+            x * x,
+            y * y,
+            z * z,
+            2.0 * x * y,
+            2.0 * x * z,
+            2.0 * y * z,
+            2.0 * x,
+            2.0 * y,
+            2.0 * z,
+        ];
+        for i in 0..9 {
+            for j in 0..9 {
+                self.ata[i][j] += row[i] * row[j];
+            }
+            self.atb[i] += row[i];
+        }
+
+        for i in 0..3 {
+            self.sum[i] += s[i];
+            for j in 0..3 {
+                self.sum_sq[i][j] += s[i] * s[j];
+            }
+        }
+
+        self.count += 1;
+    }
+
+    /// Fits an ellipsoid to the collected samples.
+    ///
+    /// Returns `None` if fewer than [`SOFT_IRON_MIN_SAMPLES`] samples were collected, or if the
+    /// samples do not describe an ellipsoid, e.g. because they do not cover enough directions.
+    pub fn fit(&self) -> Option<SoftIronFit> {
+        if self.count < SOFT_IRON_MIN_SAMPLES {
+            return None;
+        }
+
+        // a*x^2 + b*y^2 + c*z^2 + 2*d*xy + 2*e*xz + 2*f*yz + 2*g*x + 2*h*y + 2*i*z = 1
+        let p = solve(self.ata, self.atb)?;
+        let m = [[p[0], p[3], p[4]], [p[3], p[1], p[5]], [p[4], p[5], p[2]]];
+        let linear = [p[6], p[7], p[8]];
+
+        // Center: m * c = -linear
+        let center = solve(m, linear.map(|v| -v))?;
+        // (s - c)^T m (s - c) = 1 + c^T m c
+        let k = 1.0
+            + (0..3)
+                .map(|i| center[i] * (0..3).map(|j| m[i][j] * center[j]).sum::<f64>())
+                .sum::<f64>();
+        if k <= 0.0 {
+            return None;
+        }
+
+        let (eigenvalues, eigenvectors) = symmetric_eigen(m.map(|row| row.map(|v| v / k)));
+        if eigenvalues.iter().any(|&l| l <= 0.0) {
+            return None;
+        }
+
+        // The correction is the square root of the normalized quadratic form, scaled so that the
+        // corrected sphere keeps the volume of the ellipsoid.
+        let radius = libm::pow(eigenvalues.iter().product::<f64>(), -1.0 / 6.0);
+        let mut sqrt_diag = [[0.0; 3]; 3];
+        for axis in 0..3 {
+            sqrt_diag[axis][axis] = libm::sqrt(eigenvalues[axis]) * radius;
+        }
+        let correction = mat_mul(
+            &mat_mul(&eigenvectors, &sqrt_diag),
+            &transpose(&eigenvectors),
+        );
+
+        Some(SoftIronFit {
+            offset: center.map(|v| (v / SAMPLE_SCALE) as f32),
+            matrix: correction.map(|row| row.map(|v| v as f32)),
+            radius: (radius / SAMPLE_SCALE) as f32,
+            residual: self.residual(&p, k) as f32,
+            coverage: self.coverage(&correction, radius) as f32,
+        })
+    }
+
+    fn residual(&self, p: &[f64; 9], k: f64) -> f64 {
+        // Sum of the squared algebraic errors, expanded from the normal equations:
+        // |A p - 1|^2 = p^T (A^T A) p - 2 p^T (A^T 1) + n
+        let n = f64::from(self.count);
+        let quad: f64 = (0..9)
+            .map(|i| p[i] * (0..9).map(|j| self.ata[i][j] * p[j]).sum::<f64>())
+            .sum();
+        let lin: f64 = (0..9).map(|i| p[i] * self.atb[i]).sum();
+        let mean_sq = ((quad - 2.0 * lin + n) / n).max(0.0);
+
+        // The algebraic error of a sample is |u|^2 - 1 scaled by k, where u is the corrected
+        // sample on the unit sphere; for small errors this is twice the relative radial error.
+        libm::sqrt(mean_sq) / (2.0 * k)
+    }
+
+    fn coverage(&self, correction: &[[f64; 3]; 3], radius: f64) -> f64 {
+        let n = f64::from(self.count);
+        let mean = self.sum.map(|v| v / n);
+        let mut cov = [[0.0; 3]; 3];
+        for (i, row) in cov.iter_mut().enumerate() {
+            for (j, val) in row.iter_mut().enumerate() {
+                *val = self.sum_sq[i][j] / n - mean[i] * mean[j];
+            }
+        }
+
+        // Samples evenly distributed over a sphere of radius r have a covariance of r^2 / 3 * I.
+        let corrected = mat_mul(&mat_mul(correction, &cov), &transpose(correction));
+        let normalized = corrected.map(|row| row.map(|v| v * 3.0 / (radius * radius)));
+        let (spread, _) = symmetric_eigen(normalized);
+
+        spread
+            .iter()
+            .copied()
+            .fold(f64::INFINITY, f64::min)
+            .clamp(0.0, 1.0)
+    }
+}
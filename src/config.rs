@@ -0,0 +1,151 @@
+use crate::Error;
+use crate::prelude::*;
+
+/// Sensor configuration covering the `CFG_REG_A`, `CFG_REG_B` and `CFG_REG_C` registers.
+///
+/// The whole configuration can be written with `apply_config` and read back with `read_config`,
+/// each in a single bus transaction, instead of one read-modify-write cycle per setting.
+///
+/// `Config::default()` matches the power-on values of the three registers.
+///
+/// The one-shot command bits (`soft_rst`, `reboot`) and the `self_test` bit are not part of the
+/// configuration and are always written as `0`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct Config {
+    /// Operating mode.
+    pub md: Md,
+    /// Output data rate.
+    pub odr: Odr,
+    /// Power mode (high-resolution or low-power).
+    pub lp: Lp,
+    /// Magnetometer temperature compensation.
+    pub comp_temp_en: u8,
+    /// Low-pass filter bandwidth.
+    pub lpf: Lpf,
+    /// Set/reset pulse mode.
+    pub set_rst: SetRst,
+    /// Interrupt recognition before/after hard-iron correction.
+    pub int_on_dataoff: IntOnDataOff,
+    /// Offset cancellation in single measurement mode. Requires `SetRst::SensOffCancEveryOdr`.
+    pub off_canc_one_shot: u8,
+    /// Data-ready signal on the INT/DRDY pin.
+    pub drdy_on_pin: u8,
+    /// Output data byte order.
+    pub ble: Ble,
+    /// Block data update.
+    pub bdu: u8,
+    /// I2C interface enable/disable.
+    pub i2c_dis: I2cDis,
+    /// Interrupt signal on the INT/DRDY pin.
+    pub int_on_pin: u8,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            // The device powers on in idle mode, unlike `Md::default()`
+            md: Md::Idle,
+            odr: Odr::default(),
+            lp: Lp::default(),
+            comp_temp_en: 0,
+            lpf: Lpf::default(),
+            set_rst: SetRst::default(),
+            int_on_dataoff: IntOnDataOff::default(),
+            off_canc_one_shot: 0,
+            drdy_on_pin: 0,
+            ble: Ble::default(),
+            bdu: 0,
+            i2c_dis: I2cDis::default(),
+            int_on_pin: 0,
+        }
+    }
+}
+
+impl Config {
+    /// Encodes the configuration into the values of the `CFG_REG_A`, `CFG_REG_B` and `CFG_REG_C`
+    /// registers.
+    pub fn to_registers(&self) -> (CfgRegA, CfgRegB, CfgRegC) {
+        let reg_a = CfgRegA::new()
+            .with_md(self.md as u8)
+            .with_odr(self.odr as u8)
+            .with_lp(self.lp as u8)
+            .with_soft_rst(0)
+            .with_reboot(0)
+            .with_comp_temp_en(self.comp_temp_en);
+        let reg_b = CfgRegB::new()
+            .with_lpf(self.lpf as u8)
+            .with_set_rst(self.set_rst as u8)
+            .with_int_on_dataoff(self.int_on_dataoff as u8)
+            .with_off_canc_one_shot(self.off_canc_one_shot);
+        let reg_c = CfgRegC::new()
+            .with_drdy_on_pin(self.drdy_on_pin)
+            .with_self_test(0)
+            .with_ble(self.ble as u8)
+            .with_bdu(self.bdu)
+            .with_i2c_dis(self.i2c_dis as u8)
+            .with_int_on_pin(self.int_on_pin);
+
+        (reg_a, reg_b, reg_c)
+    }
+
+    /// Decodes the configuration from the values of the `CFG_REG_A`, `CFG_REG_B` and `CFG_REG_C`
+    /// registers.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::InvalidRegisterValue`: This error is returned if a field holds a value that cannot be decoded.
+    pub fn from_registers<E>(
+        reg_a: CfgRegA,
+        reg_b: CfgRegB,
+        reg_c: CfgRegC,
+    ) -> Result<Self, Error<E>> {
+        let invalid = |reg: Reg, value: u8| Error::InvalidRegisterValue { reg, value };
+
+        Ok(Self {
+            md: Md::try_from(reg_a.md()).map_err(|_| invalid(Reg::CfgRegA, reg_a.md()))?,
+            odr: Odr::try_from(reg_a.odr()).map_err(|_| invalid(Reg::CfgRegA, reg_a.odr()))?,
+            lp: Lp::try_from(reg_a.lp()).map_err(|_| invalid(Reg::CfgRegA, reg_a.lp()))?,
+            comp_temp_en: reg_a.comp_temp_en(),
+            lpf: Lpf::try_from(reg_b.lpf()).map_err(|_| invalid(Reg::CfgRegB, reg_b.lpf()))?,
+            set_rst: SetRst::try_from(reg_b.set_rst())
+                .map_err(|_| invalid(Reg::CfgRegB, reg_b.set_rst()))?,
+            int_on_dataoff: IntOnDataOff::try_from(reg_b.int_on_dataoff())
+                .map_err(|_| invalid(Reg::CfgRegB, reg_b.int_on_dataoff()))?,
+            off_canc_one_shot: reg_b.off_canc_one_shot(),
+            drdy_on_pin: reg_c.drdy_on_pin(),
+            ble: Ble::try_from(reg_c.ble()).map_err(|_| invalid(Reg::CfgRegC, reg_c.ble()))?,
+            bdu: reg_c.bdu(),
+            i2c_dis: I2cDis::try_from(reg_c.i2c_dis())
+                .map_err(|_| invalid(Reg::CfgRegC, reg_c.i2c_dis()))?,
+            int_on_pin: reg_c.int_on_pin(),
+        })
+    }
+
+    /// Checks that the configuration can be written to the device.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::InvalidConfiguration`: This error is returned if a flag is outside `0..=1`, or
+    ///   if `off_canc_one_shot` is enabled while the `OFF_CANC` bit of `set_rst` is cleared.
+    pub fn validate<E>(&self) -> Result<(), Error<E>> {
+        let flags = [
+            self.comp_temp_en,
+            self.off_canc_one_shot,
+            self.drdy_on_pin,
+            self.bdu,
+            self.int_on_pin,
+        ];
+        if flags.iter().any(|&flag| flag > 1) {
+            return Err(Error::InvalidConfiguration);
+        }
+
+        // Offset cancellation in single measurement mode requires OFF_CANC = 1.
+        if self.off_canc_one_shot == 1 && self.set_rst as u8 & 0x01 == 0 {
+            return Err(Error::InvalidConfiguration);
+        }
+
+        Ok(())
+    }
+}
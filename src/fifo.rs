@@ -0,0 +1,148 @@
+/// Magnetic sample stored in a [`SampleFifo`].
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct FifoSample {
+    /// Raw X, Y, Z values, as returned by `magnetic_raw_get`.
+    pub raw: [i16; 3],
+    /// Timestamp supplied by the application when the sample was read, in its own time base.
+    pub timestamp: u64,
+    /// At least one sample was overwritten by the sensor before this one was read (`zyxor`).
+    pub overrun: bool,
+}
+
+impl FifoSample {
+    const EMPTY: Self = Self {
+        raw: [0; 3],
+        timestamp: 0,
+        overrun: false,
+    };
+}
+
+/// Fixed-capacity software FIFO of magnetic samples.
+///
+/// The IIS2MDC has no hardware FIFO: each new sample overwrites the output registers. This queue
+/// is filled by `sample_fifo_update` on each data-ready event and keeps track of the samples lost
+/// along the way:
+///
+/// - samples overwritten in the sensor before being read, signalled by the `zyxor` bit of the
+///   `STATUS_REG` register. The sensor only reports that *at least* one sample was lost, so each
+///   overrun is counted as one lost sample;
+/// - samples discarded because the queue was full. The oldest sample is dropped to make room for
+///   the new one.
+///
+/// The queue is `no_std` and allocation-free, its capacity `N` is fixed at compile time.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SampleFifo<const N: usize> {
+    buf: [FifoSample; N],
+    head: usize,
+    len: usize,
+    overruns: u32,
+    dropped: u32,
+}
+
+impl<const N: usize> Default for SampleFifo<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> SampleFifo<N> {
+    /// Creates an empty queue.
+    pub const fn new() -> Self {
+        Self {
+            buf: [FifoSample::EMPTY; N],
+            head: 0,
+            len: 0,
+            overruns: 0,
+            dropped: 0,
+        }
+    }
+
+    /// Returns the maximum number of samples the queue can hold.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Returns the number of samples in the queue.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the queue holds no sample.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns `true` if the next push drops the oldest sample.
+    pub fn is_full(&self) -> bool {
+        self.len == N
+    }
+
+    /// Appends a sample, dropping the oldest one if the queue is full.
+    ///
+    /// A sample flagged with `overrun` is counted as one sample lost in the sensor.
+    pub fn push(&mut self, sample: FifoSample) {
+        if N == 0 {
+            self.dropped = self.dropped.saturating_add(1);
+            return;
+        }
+
+        if sample.overrun {
+            self.overruns = self.overruns.saturating_add(1);
+        }
+
+        if self.len == N {
+            self.head = (self.head + 1) % N;
+            self.len -= 1;
+            self.dropped = self.dropped.saturating_add(1);
+        }
+        self.buf[(self.head + self.len) % N] = sample;
+        self.len += 1;
+    }
+
+    /// Removes and returns the oldest sample.
+    pub fn pop(&mut self) -> Option<FifoSample> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let sample = self.buf[self.head];
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(sample)
+    }
+
+    /// Returns the oldest sample without removing it.
+    pub fn peek(&self) -> Option<&FifoSample> {
+        (self.len > 0).then(|| &self.buf[self.head])
+    }
+
+    /// Removes all the samples. The lost-sample counters are kept.
+    pub fn clear(&mut self) {
+        self.head = 0;
+        self.len = 0;
+    }
+
+    /// Returns the number of overruns reported by the sensor.
+    pub fn overrun_count(&self) -> u32 {
+        self.overruns
+    }
+
+    /// Returns the number of samples dropped because the queue was full.
+    pub fn dropped_count(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Returns the total number of lost samples, in the sensor and in the queue.
+    pub fn lost_samples(&self) -> u32 {
+        self.overruns.saturating_add(self.dropped)
+    }
+
+    /// Resets the lost-sample counters.
+    pub fn reset_counters(&mut self) {
+        self.overruns = 0;
+        self.dropped = 0;
+    }
+}
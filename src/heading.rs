@@ -0,0 +1,96 @@
+//! Tilt-compensated compass heading.
+//!
+//! The magnetic field and the gravity vector must be expressed in the same right-handed frame,
+//! with X pointing forward, Y to the left and Z up when the device lies flat. In this frame an
+//! accelerometer at rest reads +1 g on Z. If the accelerometer axes are not aligned with the
+//! IIS2MDC axes on the board, they must be remapped before calling [`tilt_compensated_heading`].
+
+use crate::measurement::MagneticField;
+
+// Smallest horizontal component of the field, relative to its magnitude, for which north is
+// defined. Below it, rounding errors dominate the direction of the horizontal component.
+const MIN_HORIZONTAL_RATIO: f32 = 1e-3;
+
+/// Orientation of the device with respect to the local horizontal plane and north.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Heading {
+    /// Angle from magnetic north to the X axis, clockwise, in degrees from 0 to 360.
+    pub magnetic: f32,
+    /// Angle from true (geographic) north to the X axis, clockwise, in degrees from 0 to 360.
+    pub true_heading: f32,
+    /// Elevation of the X axis above the horizontal plane, in degrees from -90 to 90.
+    pub pitch: f32,
+    /// Rotation around the X axis, positive when the right side (-Y) is down, in degrees from
+    /// -180 to 180.
+    pub roll: f32,
+}
+
+/// Computes the tilt-compensated heading from a magnetic field and a gravity vector.
+///
+/// # Arguments
+///
+/// * `field`: The calibrated magnetic field, e.g. from `magnetic_get` or `SoftIronFit::apply`.
+/// * `gravity`: The accelerometer X, Y, Z reading, in any unit. The device must not be
+///   accelerating for the gravity direction to be valid.
+/// * `declination`: The local magnetic declination in degrees, positive when magnetic north is
+///   east of true north.
+///
+/// # Returns
+///
+/// * `Option<Heading>`: Returns `None` if the gravity vector or the field is zero, or if the field
+///   is parallel to gravity so that no horizontal component is left to point north.
+pub fn tilt_compensated_heading(
+    field: &MagneticField,
+    gravity: [f32; 3],
+    declination: f32,
+) -> Option<Heading> {
+    let up = normalize(gravity)?;
+    // B x up points east and up x east points north, whatever the tilt of the device.
+    let east = cross(normalize(field.to_array())?, up);
+    if norm(east) < MIN_HORIZONTAL_RATIO {
+        return None;
+    }
+    let east = normalize(east)?;
+    let north = cross(up, east);
+
+    let magnetic = wrap_degrees(libm::atan2f(east[0], north[0]).to_degrees());
+    let pitch = libm::atan2f(
+        gravity[0],
+        libm::sqrtf(gravity[1] * gravity[1] + gravity[2] * gravity[2]),
+    );
+    let roll = libm::atan2f(gravity[1], gravity[2]);
+
+    Some(Heading {
+        magnetic,
+        true_heading: wrap_degrees(magnetic + declination),
+        pitch: pitch.to_degrees(),
+        roll: roll.to_degrees(),
+    })
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn norm(v: [f32; 3]) -> f32 {
+    libm::sqrtf(v[0] * v[0] + v[1] * v[1] + v[2] * v[2])
+}
+
+fn normalize(v: [f32; 3]) -> Option<[f32; 3]> {
+    let norm = norm(v);
+    if norm <= f32::EPSILON {
+        return None;
+    }
+    Some(v.map(|c| c / norm))
+}
+
+fn wrap_degrees(angle: f32) -> f32 {
+    let wrapped = angle - 360.0 * libm::floorf(angle / 360.0);
+    // Rounding can push values just below 0 up to exactly 360.
+    if wrapped >= 360.0 { 0.0 } else { wrapped }
+}
@@ -8,8 +8,36 @@ use embedded_hal::i2c::{I2c, SevenBitAddress};
 use embedded_hal::spi::SpiDevice;
 use st_mems_bus::BusOperation;
 
+#[cfg(feature = "async")]
+pub mod asynchronous;
+pub mod calibration;
+pub mod config;
+pub mod fifo;
+pub mod heading;
+pub mod measurement;
 pub mod prelude;
 pub mod register;
+pub mod self_test;
+pub mod sensor_hub;
+#[cfg(feature = "sim")]
+pub mod sim;
+pub mod snapshot;
+pub mod stream;
+pub mod threshold;
+pub mod wake;
+
+use calibration::SoftIronFit;
+pub use calibration::{CalibrationData, HardIronFit};
+pub use config::Config;
+pub use fifo::{FifoSample, SampleFifo};
+pub use heading::{Heading, tilt_compensated_heading};
+pub use measurement::MagneticField;
+use self_test::SELF_TEST_SETTLING_MS;
+pub use self_test::{SELF_TEST_MAX_MG, SELF_TEST_MIN_MG, SELF_TEST_SAMPLES, SelfTestReport};
+pub use snapshot::{RegisterSnapshot, SNAPSHOT_BLOCK_LEN};
+pub use stream::{RAW_SAMPLE_LEN, RawSample, Sample, SampleIter};
+pub use threshold::{ThresholdConfig, ThresholdEvent};
+pub use wake::{WAKE_AMBIENT_SAMPLES, WakeOnMagnet};
 
 /// The Iis2mdc generic driver struct.
 pub struct Iis2mdc<B, T> {
@@ -26,6 +54,147 @@ pub enum Error<B> {
     ///
     /// The generic type B represents the specific error generated by the HAL of the microcontroller in use.
     Bus(B),
+    /// An error occurred while waiting on a GPIO connected to the INT/DRDY pin.
+    ///
+    /// Only returned by the methods of the asynchronous driver that await an interrupt pin.
+    Pin(embedded_hal::digital::ErrorKind),
+    /// The value read from the `WHO_AM_I` register does not match [`IIS2MDC_ID`].
+    ///
+    /// The contained value is the identifier actually read from the device.
+    InvalidDeviceId(u8),
+    /// The sensor ID stamped in a calibration record does not match the device it is restored on.
+    SensorIdMismatch {
+        /// The sensor ID stored in the record.
+        stored: u8,
+        /// The identifier read from the `WHO_AM_I` register.
+        device: u8,
+    },
+    /// The device did not complete an operation within the allowed number of retries.
+    Timeout,
+    /// A register field holds a value that does not match any of the documented encodings.
+    InvalidRegisterValue {
+        /// The register that was read.
+        reg: Reg,
+        /// The undecodable field value.
+        value: u8,
+    },
+    /// The self-test procedure completed, but at least one axis is out of the expected range.
+    SelfTestFailed,
+    /// The requested configuration is not valid, e.g. a flag outside `0..=1` or offset
+    /// cancellation in single measurement mode without `OFF_CANC`.
+    InvalidConfiguration,
+}
+
+impl<B> From<B> for Error<B> {
+    fn from(err: B) -> Self {
+        Error::Bus(err)
+    }
+}
+
+impl<B: Debug> core::fmt::Display for Error<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::Bus(err) => write!(f, "bus error: {err:?}"),
+            Error::Pin(kind) => write!(f, "interrupt pin error: {kind:?}"),
+            Error::InvalidDeviceId(id) => {
+                write!(
+                    f,
+                    "invalid device id: {id:#04x} (expected {IIS2MDC_ID:#04x})"
+                )
+            }
+            Error::SensorIdMismatch { stored, device } => {
+                write!(
+                    f,
+                    "calibration taken on sensor id {stored:#04x}, device id is {device:#04x}"
+                )
+            }
+            Error::Timeout => write!(f, "timeout while waiting for the device"),
+            Error::InvalidRegisterValue { reg, value } => {
+                write!(f, "invalid value {value:#04x} in register {reg:?}")
+            }
+            Error::SelfTestFailed => write!(f, "self-test failed"),
+            Error::InvalidConfiguration => write!(f, "invalid configuration"),
+        }
+    }
+}
+
+impl<B: Debug> core::error::Error for Error<B> {}
+
+#[cfg(feature = "defmt")]
+impl<B: defmt::Format> defmt::Format for Error<B> {
+    fn format(&self, f: defmt::Formatter) {
+        match self {
+            Error::Bus(err) => defmt::write!(f, "bus error: {}", err),
+            Error::Pin(kind) => {
+                defmt::write!(f, "interrupt pin error: {}", defmt::Debug2Format(kind))
+            }
+            Error::InvalidDeviceId(id) => defmt::write!(
+                f,
+                "invalid device id: {=u8:#04x} (expected {=u8:#04x})",
+                id,
+                IIS2MDC_ID
+            ),
+            Error::SensorIdMismatch { stored, device } => defmt::write!(
+                f,
+                "calibration taken on sensor id {=u8:#04x}, device id is {=u8:#04x}",
+                stored,
+                device
+            ),
+            Error::Timeout => defmt::write!(f, "timeout while waiting for the device"),
+            Error::InvalidRegisterValue { reg, value } => {
+                defmt::write!(f, "invalid value {=u8:#04x} in register {}", value, reg)
+            }
+            Error::SelfTestFailed => defmt::write!(f, "self-test failed"),
+            Error::InvalidConfiguration => defmt::write!(f, "invalid configuration"),
+        }
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<B: ufmt::uDebug> ufmt::uDebug for Error<B> {
+    fn fmt<W: ufmt::uWrite + ?Sized>(
+        &self,
+        f: &mut ufmt::Formatter<'_, W>,
+    ) -> Result<(), W::Error> {
+        match self {
+            Error::Bus(err) => f.debug_tuple("Bus")?.field(err)?.finish(),
+            // ErrorKind does not implement uDebug
+            Error::Pin(embedded_hal::digital::ErrorKind::Other) => f.write_str("Pin(Other)"),
+            Error::Pin(_) => f.write_str("Pin(..)"),
+            Error::InvalidDeviceId(id) => f.debug_tuple("InvalidDeviceId")?.field(id)?.finish(),
+            Error::SensorIdMismatch { stored, device } => f
+                .debug_struct("SensorIdMismatch")?
+                .field("stored", stored)?
+                .field("device", device)?
+                .finish(),
+            Error::Timeout => f.write_str("Timeout"),
+            Error::InvalidRegisterValue { reg, value } => f
+                .debug_struct("InvalidRegisterValue")?
+                .field("reg", reg)?
+                .field("value", value)?
+                .finish(),
+            Error::SelfTestFailed => f.write_str("SelfTestFailed"),
+            Error::InvalidConfiguration => f.write_str("InvalidConfiguration"),
+        }
+    }
+}
+
+impl<B: embedded_hal::i2c::Error> embedded_hal::i2c::Error for Error<B> {
+    fn kind(&self) -> embedded_hal::i2c::ErrorKind {
+        match self {
+            Error::Bus(err) => err.kind(),
+            _ => embedded_hal::i2c::ErrorKind::Other,
+        }
+    }
+}
+
+impl<B: embedded_hal::spi::Error> embedded_hal::spi::Error for Error<B> {
+    fn kind(&self) -> embedded_hal::spi::ErrorKind {
+        match self {
+            Error::Bus(err) => err.kind(),
+            _ => embedded_hal::spi::ErrorKind::Other,
+        }
+    }
 }
 
 impl<P, T> Iis2mdc<st_mems_bus::i2c::I2cBus<P>, T>
@@ -55,14 +224,13 @@ where
     B: BusOperation,
     T: DelayNs,
 {
-    /// Create a safe fake buffer to use the sensor as master of the
-    /// sensor hub.
+    /// Constructor method for using any bus that implements BusOperation, e.g. a
+    /// [`sensor_hub::SensorHubBus`] to reach the sensor through the sensor hub of an IMU.
     ///
     /// # Arguments
     ///
     /// * `bus`: The bus that implements BusOperation.
     /// * `tim`: The timer of the COMPONENT sensor.
-    /// * `slave_address`: The I2C address of the slave sensor
     ///
     /// # Returns
     ///
@@ -72,6 +240,28 @@ where
     }
 }
 
+impl<M, T> Iis2mdc<sensor_hub::SensorHubBus<M>, T>
+where
+    M: sensor_hub::SensorHubMaster,
+    T: DelayNs,
+{
+    /// Constructor method for using the sensor as an external sensor of an IMU, through its
+    /// sensor hub.
+    ///
+    /// # Arguments
+    ///
+    /// * `master`: The sensor-hub master of the IMU.
+    /// * `tim`: The timer of the COMPONENT sensor.
+    ///
+    /// # Returns
+    ///
+    /// * `Self`: Returns an instance of `Iis2mdc`.
+    pub fn new_sensor_hub(master: M, tim: T) -> Self {
+        let bus = sensor_hub::SensorHubBus::new(master, I2CAddress::I2cAdd);
+        Self { bus, tim }
+    }
+}
+
 impl<P, T> Iis2mdc<st_mems_bus::spi::SpiBus<P>, T>
 where
     P: SpiDevice,
@@ -157,6 +347,100 @@ impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
         Ok([val.x, val.y, val.z])
     }
 
+    /// Applies a hard-iron fit to the offset registers.
+    ///
+    /// This function reads the hard-iron offset currently programmed in the `OFFSET_X/Y/Z_REG`
+    /// registers, adds the offset estimated by the fit and writes the result with
+    /// `mag_user_offset_set`. The samples used for the fit must have been collected with the
+    /// current offset in place.
+    ///
+    /// # Arguments
+    ///
+    /// * `fit`: The result of `HardIronCalibrator::fit`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<[i16; 3], Error<B::Error>>`: Returns the offset written to the sensor, in LSB (1 LSB = 1.5 mG). Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub fn hard_iron_offset_apply(
+        &mut self,
+        fit: &HardIronFit,
+    ) -> Result<[i16; 3], Error<B::Error>> {
+        let current = self.mag_user_offset_get()?;
+        let mut offset = [0; 3];
+        (0..3).for_each(|axis| offset[axis] = current[axis].saturating_add(fit.offset[axis]));
+        self.mag_user_offset_set(&offset)?;
+
+        Ok(offset)
+    }
+
+    /// Reads the calibration currently in use into a [`CalibrationData`] record.
+    ///
+    /// This function reads the device ID, the hard-iron offset from the `OFFSET_X/Y/Z_REG`
+    /// registers and the current temperature, and stores them together with the soft-iron matrix
+    /// and offset computed by the application.
+    ///
+    /// # Arguments
+    ///
+    /// * `soft_iron`: The soft-iron correction, fitted on samples taken with the current hard-iron
+    ///   offset. Use `SoftIronFit::default()` with an identity matrix if there is none.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<CalibrationData, Error<B::Error>>`: Returns the calibration record. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub fn calibration_data_get(
+        &mut self,
+        soft_iron: &SoftIronFit,
+    ) -> Result<CalibrationData, Error<B::Error>> {
+        Ok(CalibrationData {
+            sensor_id: self.device_id_get()?,
+            hard_iron: self.mag_user_offset_get()?,
+            soft_iron: soft_iron.matrix,
+            soft_iron_offset: soft_iron.offset,
+            temperature: self.temperature_get()?,
+        })
+    }
+
+    /// Restores a stored calibration.
+    ///
+    /// This function reads the device ID from the `WHO_AM_I` register, checks that it matches the
+    /// sensor ID stored in the record and writes the hard-iron offset back through
+    /// `mag_user_offset_set`. The soft-iron correction is applied in software with
+    /// `CalibrationData::soft_iron_fit` and is not written to the sensor.
+    ///
+    /// The sensor ID identifies the part type, not the unit: a record taken from another IIS2MDC is
+    /// accepted. Keeping the calibration with the board it was taken on is up to the application.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The calibration record, e.g. from `CalibrationData::deserialize`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the offset is successfully written. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::SensorIdMismatch { stored, device }`: This error is returned if the record was stamped with a sensor ID different from the device ID.
+    pub fn restore_calibration(&mut self, val: &CalibrationData) -> Result<(), Error<B::Error>> {
+        let device = self.device_id_get()?;
+        if val.sensor_id != device {
+            return Err(Error::SensorIdMismatch {
+                stored: val.sensor_id,
+                device,
+            });
+        }
+        self.mag_user_offset_set(&val.hard_iron)
+    }
+
     /// Sets the operating mode of the sensor.
     ///
     /// This function modifies the operating mode by updating the `md` field in the `CFG_REG_A` register.
@@ -189,9 +473,13 @@ impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
     /// # Errors
     ///
     /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
     pub fn operating_mode_get(&mut self) -> Result<Md, Error<B::Error>> {
         let val = CfgRegA::read(self)?.md();
-        Ok(Md::try_from(val).unwrap_or_default())
+        Md::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegA,
+            value: val,
+        })
     }
 
     /// Sets the output data rate of the sensor.
@@ -226,9 +514,13 @@ impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
     /// # Errors
     ///
     /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
     pub fn data_rate_get(&mut self) -> Result<Odr, Error<B::Error>> {
         let val = CfgRegA::read(self)?.odr();
-        Ok(Odr::try_from(val).unwrap_or_default())
+        Odr::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegA,
+            value: val,
+        })
     }
 
     /// Sets the power mode of the sensor to high-resolution or low-power.
@@ -263,9 +555,13 @@ impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
     /// # Errors
     ///
     /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
     pub fn power_mode_get(&mut self) -> Result<Lp, Error<B::Error>> {
         let val = CfgRegA::read(self)?.lp();
-        Ok(Lp::try_from(val).unwrap_or_default())
+        Lp::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegA,
+            value: val,
+        })
     }
 
     /// Enables or disables the magnetometer temperature compensation.
@@ -336,9 +632,13 @@ impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
     /// # Errors
     ///
     /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
     pub fn low_pass_bandwidth_get(&mut self) -> Result<Lpf, Error<B::Error>> {
         let val = CfgRegB::read(self)?.lpf();
-        Ok(Lpf::try_from(val).unwrap_or_default())
+        Lpf::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegB,
+            value: val,
+        })
     }
 
     /// Sets the reset pulse mode.
@@ -373,9 +673,13 @@ impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
     /// # Errors
     ///
     /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
     pub fn set_rst_mode_get(&mut self) -> Result<SetRst, Error<B::Error>> {
         let val = CfgRegB::read(self)?.set_rst();
-        Ok(SetRst::try_from(val).unwrap_or_default())
+        SetRst::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegB,
+            value: val,
+        })
     }
 
     /// Enables offset cancellation in single measurement mode.
@@ -481,6 +785,36 @@ impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
         Ok(StatusReg::read(self)?.zyxor())
     }
 
+    /// Waits until a new set of magnetic data is available.
+    ///
+    /// This function polls the `zyxda` field of the `STATUS_REG` register, waiting 1 ms between
+    /// two consecutive reads, until new data is available or the timeout expires.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeout_ms`: The maximum time to wait, in milliseconds.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` as soon as new data is available. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::Timeout`: This error is returned if no new data is available within `timeout_ms`.
+    pub fn mag_data_ready_wait(&mut self, timeout_ms: u32) -> Result<(), Error<B::Error>> {
+        let mut elapsed = 0;
+        while self.mag_data_ready_get()? == 0 {
+            if elapsed >= timeout_ms {
+                return Err(Error::Timeout);
+            }
+            self.tim.delay_ms(DRDY_POLL_DELAY_MS);
+            elapsed += DRDY_POLL_DELAY_MS;
+        }
+
+        Ok(())
+    }
+
     // Retrieves the raw magnetic output values.
     ///
     /// This function reads the magnetic output values from the sensor's registers, returning them as a three-element array of 16-bit integers.
@@ -497,6 +831,22 @@ impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
         Ok([val.x, val.y, val.z])
     }
 
+    /// Retrieves the magnetic field in milligauss.
+    ///
+    /// This function reads the magnetic output values from the sensor's registers and applies the
+    /// 1.5 mG/LSB sensitivity.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<MagneticField, Error<B::Error>>`: Returns the magnetic field on the X, Y, Z axes. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub fn magnetic_get(&mut self) -> Result<MagneticField, Error<B::Error>> {
+        Ok(MagneticField::from_raw(self.magnetic_raw_get()?))
+    }
+
     //// Retrieves the raw temperature output value.
     ///
     /// This function reads the temperature value from the sensor's registers, returning it as a 16-bit integer.
@@ -512,6 +862,70 @@ impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
         Ok(TempOutReg::read(self)?.temp_out())
     }
 
+    /// Retrieves the temperature in degrees Celsius.
+    ///
+    /// This function reads the temperature output register and converts it with [`from_lsb_to_celsius`].
+    ///
+    /// # Returns
+    ///
+    /// * `Result<f32, Error<B::Error>>`: Returns the temperature in degrees Celsius. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub fn temperature_get(&mut self) -> Result<f32, Error<B::Error>> {
+        Ok(from_lsb_to_celsius(self.temperature_raw_get()?))
+    }
+
+    /// Retrieves the status, magnetic and temperature output values in a single burst.
+    ///
+    /// This function reads the registers from `STATUS_REG` (0x67) to `TEMP_OUT_H_REG` (0x6F) in
+    /// one bus transaction, instead of one transaction each for `status_get`,
+    /// `magnetic_raw_get` and `temperature_raw_get`. The temperature is guaranteed to belong to
+    /// the same sample as the field.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RawSample, Error<B::Error>>`: Returns the raw status, magnetic and temperature values. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub fn sample_raw_get(&mut self) -> Result<RawSample, Error<B::Error>> {
+        let mut buff = [0u8; RAW_SAMPLE_LEN];
+        self.read_from_register(Reg::StatusReg as u8, &mut buff)?;
+
+        Ok(RawSample::from_bytes(buff))
+    }
+
+    /// Returns an iterator over the samples of the sensor.
+    ///
+    /// This function reads the output data rate from the `CFG_REG_A` register to pace the
+    /// iterator: each sample is awaited by polling the `STATUS_REG` register a few times per
+    /// sample period, sleeping with the delay of the driver in between. The device must be in
+    /// continuous mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `temperature`: Whether to report the temperature with each sample.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SampleIter<'_, B, T>, Error<B::Error>>`: Returns the iterator. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
+    pub fn samples(&mut self, temperature: bool) -> Result<SampleIter<'_, B, T>, Error<B::Error>> {
+        let period_ms = odr_period_ms(self.data_rate_get()?);
+        Ok(SampleIter {
+            sensor: self,
+            period_ms,
+            temperature,
+        })
+    }
+
     /// Retrieves the device ID.
     ///
     /// This function reads the device ID from the sensor's registers, returning it as an 8-bit integer.
@@ -670,9 +1084,13 @@ impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
     /// # Errors
     ///
     /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
     pub fn data_format_get(&mut self) -> Result<Ble, Error<B::Error>> {
         let val = CfgRegC::read(self)?.ble();
-        Ok(Ble::try_from(val).unwrap_or_default())
+        Ble::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegC,
+            value: val,
+        })
     }
 
     /// Retrieves the device status information.
@@ -690,6 +1108,44 @@ impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
         StatusReg::read(self)
     }
 
+    /// Reads a new magnetic sample, if any, into a software FIFO.
+    ///
+    /// This function reads the status and output registers in a single burst and, when a new set
+    /// of data is available (`zyxda`), pushes the sample to the queue, flagged with the data
+    /// overrun bit (`zyxor`). It is meant to be called on each data-ready event, e.g. from
+    /// the INT/DRDY pin interrupt.
+    ///
+    /// # Arguments
+    ///
+    /// * `fifo`: The queue receiving the sample.
+    /// * `timestamp`: The timestamp stored with the sample, in the application time base.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<bool, Error<B::Error>>`: Returns `true` if a sample was pushed, `false` if no new data was available. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub fn sample_fifo_update<const N: usize>(
+        &mut self,
+        fifo: &mut SampleFifo<N>,
+        timestamp: u64,
+    ) -> Result<bool, Error<B::Error>> {
+        let sample = self.sample_raw_get()?;
+        if sample.status.zyxda() == 0 {
+            return Ok(false);
+        }
+
+        fifo.push(FifoSample {
+            raw: sample.magnetic,
+            timestamp,
+            overrun: sample.status.zyxor() == 1,
+        });
+
+        Ok(true)
+    }
+
     /// Configures the interrupt block recognition for data checks after/before hard-iron correction.
     ///
     /// This function modifies the `int_on_dataoff` field in the `CFG_REG_B` register to set the interrupt configuration.
@@ -722,9 +1178,13 @@ impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
     /// # Errors
     ///
     /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
     pub fn offset_int_conf_get(&mut self) -> Result<IntOnDataOff, Error<B::Error>> {
         let val = CfgRegB::read(self)?.int_on_dataoff();
-        Ok(IntOnDataOff::try_from(val).unwrap_or_default())
+        IntOnDataOff::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegB,
+            value: val,
+        })
     }
 
     /// Sets the data-ready signal on the INT_DRDY pin.
@@ -882,6 +1342,66 @@ impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
         Ok(IntThsReg::read(self)?.int_ths())
     }
 
+    /// Configures the threshold interrupt generator.
+    ///
+    /// This function writes the threshold to the `INT_THS_L/H_REG` registers, converted from
+    /// milligauss to LSB, and then the per-axis enables, latch, polarity and generator enable to
+    /// the `INT_CTRL_REG` register. Routing the interrupt to the INT/DRDY pin is controlled
+    /// separately with `int_on_pin_set`.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The threshold interrupt configuration, represented by the `ThresholdConfig` struct.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the configuration is successfully written. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidConfiguration`: This error is returned if the configuration is not valid.
+    pub fn threshold_event_set(&mut self, val: &ThresholdConfig) -> Result<(), Error<B::Error>> {
+        val.validate()?;
+        let (ctrl, ths) = val.to_registers();
+        ths.write(self)?;
+        ctrl.write(self)
+    }
+
+    /// Retrieves the threshold interrupt generator configuration.
+    ///
+    /// This function reads the `INT_CTRL_REG` and `INT_THS_L/H_REG` registers.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ThresholdConfig, Error<B::Error>>`: Returns the current configuration. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
+    pub fn threshold_event_get(&mut self) -> Result<ThresholdConfig, Error<B::Error>> {
+        let ctrl = IntCtrlReg::read(self)?;
+        let ths = IntThsReg::read(self)?;
+        ThresholdConfig::from_registers(ctrl, ths)
+    }
+
+    /// Retrieves the decoded threshold interrupt event.
+    ///
+    /// This function reads the `INT_SOURCE_REG` register. When the interrupt is latched, reading
+    /// it also clears the interrupt.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<ThresholdEvent, Error<B::Error>>`: Returns the decoded event. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub fn threshold_event_source_get(&mut self) -> Result<ThresholdEvent, Error<B::Error>> {
+        Ok(ThresholdEvent::from_register(self.int_gen_source_get()?))
+    }
+
     /// Enables or disables the I2C interface.
     ///
     /// This function modifies the `i2c_dis` field in the `CFG_REG_C` register to enable or disable the I2C interface.
@@ -914,9 +1434,383 @@ impl<B: BusOperation, T: DelayNs> Iis2mdc<B, T> {
     /// # Errors
     ///
     /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the register holds a value that cannot be decoded.
     pub fn i2c_interface_get(&mut self) -> Result<I2cDis, Error<B::Error>> {
         let val = CfgRegC::read(self)?.i2c_dis();
-        Ok(I2cDis::try_from(val).unwrap_or_default())
+        I2cDis::try_from(val).map_err(|_| Error::InvalidRegisterValue {
+            reg: Reg::CfgRegC,
+            value: val,
+        })
+    }
+
+    /// Triggers a single measurement and returns its raw magnetic output values.
+    ///
+    /// This function discards any pending sample, writes the `off_canc_one_shot` field of the
+    /// `CFG_REG_B` register, triggers a measurement by setting the operating mode to
+    /// `Md::SingleTrigger` and waits for the `zyxda` field of the `STATUS_REG` register. After the
+    /// measurement, the device automatically goes back to idle mode.
+    ///
+    /// Offset cancellation in single measurement mode requires the `set_rst` field to be set to
+    /// `SetRst::SensOffCancEveryOdr` in the same write as `off_canc_one_shot`. The previous content
+    /// of `CFG_REG_B` is written back once the measurement is over, whether it succeeded or not, so
+    /// the `set_rst` mode configured by the application is left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `off_canc`: `1` to cancel the offset for this measurement, `0` otherwise.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<[i16; 3], Error<B::Error>>`: Returns the raw X, Y, Z values of the triggered measurement, in LSB (1 LSB = 1.5 mG). Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidConfiguration`: This error is returned if `off_canc` is neither `0` nor `1`.
+    /// - `Error::Timeout`: This error is returned if the measurement does not complete in time.
+    pub fn measure_once(&mut self, off_canc: u8) -> Result<[i16; 3], Error<B::Error>> {
+        if off_canc > 1 {
+            return Err(Error::InvalidConfiguration);
+        }
+
+        // Discard a pending sample, so that only the triggered one is returned
+        if self.mag_data_ready_get()? == 1 {
+            self.magnetic_raw_get()?;
+        }
+
+        let saved = CfgRegB::read(self)?;
+        let mut reg = saved;
+        if off_canc == 1 {
+            reg.set_set_rst(SetRst::SensOffCancEveryOdr as u8);
+        }
+        reg.set_off_canc_one_shot(off_canc);
+        reg.write(self)?;
+
+        let result = self.single_measurement();
+        // Restore CFG_REG_B on both paths, reporting the measurement error first
+        if reg.into_bits() != saved.into_bits() {
+            let restored = saved.write(self);
+            let raw = result?;
+            restored?;
+            return Ok(raw);
+        }
+        result
+    }
+
+    fn single_measurement(&mut self) -> Result<[i16; 3], Error<B::Error>> {
+        self.operating_mode_set(Md::SingleTrigger)?;
+        self.mag_data_ready_wait(SINGLE_MEASUREMENT_TIMEOUT_MS)?;
+        self.magnetic_raw_get()
+    }
+
+    /// Initializes the sensor and applies the requested configuration.
+    ///
+    /// This function performs the complete start-up sequence:
+    /// 1. validates the configuration, before accessing the device;
+    /// 2. checks the `WHO_AM_I` register against [`IIS2MDC_ID`];
+    /// 3. performs a software reset and polls the `soft_rst` bit until it is cleared;
+    /// 4. reboots the memory content and polls the `reboot` bit until it is cleared;
+    /// 5. writes the configuration with `apply_config`;
+    /// 6. waits for the power-up time needed to get stable output.
+    ///
+    /// Each polling loop is bounded, so a missing or unresponsive device does not hang the caller.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired sensor configuration, represented by the `Config` struct.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the sensor is successfully initialized. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidDeviceId(u8)`: This error is returned if the device ID does not match.
+    /// - `Error::Timeout`: This error is returned if the reset or the reboot does not complete.
+    /// - `Error::InvalidConfiguration`: This error is returned if the configuration is not valid.
+    pub fn init(&mut self, val: &Config) -> Result<(), Error<B::Error>> {
+        val.validate()?;
+
+        let id = self.device_id_get()?;
+        if id != IIS2MDC_ID {
+            return Err(Error::InvalidDeviceId(id));
+        }
+
+        // Restore default configuration
+        self.reset_set(1)?;
+        let mut retries = 0;
+        while self.reset_get()? != 0 {
+            retries += 1;
+            if retries > INIT_MAX_RETRIES {
+                return Err(Error::Timeout);
+            }
+            self.tim.delay_ms(INIT_POLL_DELAY_MS);
+        }
+
+        // Reload calibration parameters
+        self.boot_set(1)?;
+        self.tim.delay_ms(BOOT_TIME_MS);
+        let mut retries = 0;
+        while self.boot_get()? != 0 {
+            retries += 1;
+            if retries > INIT_MAX_RETRIES {
+                return Err(Error::Timeout);
+            }
+            self.tim.delay_ms(INIT_POLL_DELAY_MS);
+        }
+
+        self.apply_config(val)?;
+        // Power up and wait for stable output
+        self.tim.delay_ms(POWER_UP_TIME_MS);
+
+        Ok(())
+    }
+
+    /// Runs the self-test procedure.
+    ///
+    /// This function configures the sensor in continuous mode at 100 Hz with offset cancellation,
+    /// temperature compensation and block data update, then averages [`SELF_TEST_SAMPLES`] samples
+    /// with the self-test disabled and [`SELF_TEST_SAMPLES`] samples with the self-test enabled.
+    /// The absolute difference on each axis must be within [`SELF_TEST_MIN_MG`] and
+    /// [`SELF_TEST_MAX_MG`] milligauss.
+    ///
+    /// The configuration found before the test is restored afterwards, with the self-test disabled.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<SelfTestReport, Error<B::Error>>`: Returns the per-axis report of the self-test. Use
+    ///   `SelfTestReport::check` to turn a failing report into `Error::SelfTestFailed`. Returns an
+    ///   `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the current configuration cannot be decoded.
+    /// - `Error::Timeout`: This error is returned if the sensor stops producing data.
+    pub fn self_test(&mut self) -> Result<SelfTestReport, Error<B::Error>> {
+        let previous = self.read_config()?;
+        let config = Config {
+            md: Md::ContinuousMode,
+            odr: Odr::_100hz,
+            lp: Lp::HighResolution,
+            comp_temp_en: 1,
+            set_rst: SetRst::SensOffCancEveryOdr,
+            off_canc_one_shot: 0,
+            bdu: 1,
+            ..previous
+        };
+
+        let result = self.self_test_run(&config);
+
+        // Restore the previous configuration, self-test disabled
+        self.apply_config(&previous)?;
+        result
+    }
+
+    fn self_test_run(&mut self, config: &Config) -> Result<SelfTestReport, Error<B::Error>> {
+        self.apply_config(config)?;
+        // Power up and wait for stable output
+        self.tim.delay_ms(POWER_UP_TIME_MS);
+        let baseline = self.self_test_average()?;
+
+        // Enable self test mode
+        self.self_test_set(1)?;
+        self.tim.delay_ms(SELF_TEST_SETTLING_MS);
+        let self_test = self.self_test_average()?;
+
+        Ok(SelfTestReport::new(baseline, self_test))
+    }
+
+    fn self_test_average(&mut self) -> Result<[f32; 3], Error<B::Error>> {
+        // Flush old samples
+        if self.mag_data_ready_get()? == 1 {
+            self.magnetic_raw_get()?;
+        }
+
+        let mut sum = [0.0_f32; 3];
+        for _ in 0..SELF_TEST_SAMPLES {
+            self.mag_data_ready_wait(SELF_TEST_DRDY_TIMEOUT_MS)?;
+            let field = self.magnetic_get()?.to_array();
+            (0..3).for_each(|axis| sum[axis] += field[axis]);
+        }
+
+        Ok(sum.map(|v| v / SELF_TEST_SAMPLES as f32))
+    }
+
+    /// Arms a wake-on-magnet low-power preset.
+    ///
+    /// This function measures the ambient field and programs it as hard-iron offset, so that the
+    /// threshold interrupt, checked after hard-iron correction, fires when the field deviates from
+    /// this baseline by more than `threshold_mg` on any axis. The sequence is:
+    ///
+    /// 1. disable the interrupt generator and clear the hard-iron offset;
+    /// 2. apply the preset configuration (see `WakeOnMagnet::to_config`) and wait for stable output;
+    /// 3. average [`WAKE_AMBIENT_SAMPLES`] samples and write them with `mag_user_offset_set`;
+    /// 4. enable the latched, active-high threshold interrupt and clear any pending event.
+    ///
+    /// While the preset is armed, the output registers report the deviation from the baseline.
+    ///
+    /// # Arguments
+    ///
+    /// * `preset`: The low-power preset, represented by the `WakeOnMagnet` enum.
+    /// * `threshold_mg`: The allowed deviation from the ambient field on each axis, in milligauss.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<[i16; 3], Error<B::Error>>`: Returns the ambient field written as offset, in LSB (1 LSB = 1.5 mG). Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidConfiguration`: This error is returned if the threshold is not valid.
+    /// - `Error::InvalidRegisterValue`: This error is returned if the current configuration cannot be decoded.
+    /// - `Error::Timeout`: This error is returned if no sample is produced while measuring the ambient field.
+    pub fn wake_on_magnet_set(
+        &mut self,
+        preset: WakeOnMagnet,
+        threshold_mg: f32,
+    ) -> Result<[i16; 3], Error<B::Error>> {
+        let threshold = preset.threshold_config(threshold_mg);
+        threshold.validate()?;
+        let config = preset.to_config(&self.read_config()?);
+
+        self.threshold_event_set(&ThresholdConfig {
+            ien: 0,
+            ..threshold
+        })?;
+        self.mag_user_offset_set(&[0; 3])?;
+        self.apply_config(&config)?;
+        // Power up and wait for stable output
+        self.tim.delay_ms(POWER_UP_TIME_MS);
+
+        let ambient = self.wake_on_magnet_ambient(preset)?;
+        self.mag_user_offset_set(&ambient)?;
+
+        self.threshold_event_set(&threshold)?;
+        // Clear an event latched before the baseline was in place
+        self.int_gen_source_get()?;
+
+        Ok(ambient)
+    }
+
+    fn wake_on_magnet_ambient(
+        &mut self,
+        preset: WakeOnMagnet,
+    ) -> Result<[i16; 3], Error<B::Error>> {
+        // Flush old samples
+        if self.mag_data_ready_get()? == 1 {
+            self.magnetic_raw_get()?;
+        }
+
+        let mut sum = [0_i32; 3];
+        for _ in 0..WAKE_AMBIENT_SAMPLES {
+            self.mag_data_ready_wait(preset.drdy_timeout_ms())?;
+            let raw = self.magnetic_raw_get()?;
+            (0..3).for_each(|axis| sum[axis] += raw[axis] as i32);
+        }
+
+        Ok(sum.map(|v| libm::roundf(v as f32 / WAKE_AMBIENT_SAMPLES as f32) as i16))
+    }
+
+    /// Writes the whole sensor configuration.
+    ///
+    /// This function encodes the configuration into the `CFG_REG_A`, `CFG_REG_B` and `CFG_REG_C`
+    /// registers and writes them in a single bus transaction. The `soft_rst`, `reboot` and
+    /// `self_test` bits are written as `0`.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The desired sensor configuration, represented by the `Config` struct.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the configuration is successfully written. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidConfiguration`: This error is returned if the configuration is not valid.
+    pub fn apply_config(&mut self, val: &Config) -> Result<(), Error<B::Error>> {
+        val.validate()?;
+        let (reg_a, reg_b, reg_c) = val.to_registers();
+        self.write_to_register(
+            Reg::CfgRegA as u8,
+            &[reg_a.into_bits(), reg_b.into_bits(), reg_c.into_bits()],
+        )
+    }
+
+    /// Reads back the whole sensor configuration.
+    ///
+    /// This function reads the `CFG_REG_A`, `CFG_REG_B` and `CFG_REG_C` registers in a single bus
+    /// transaction and decodes them into a `Config` struct.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Config, Error<B::Error>>`: Returns the current sensor configuration. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    /// - `Error::InvalidRegisterValue`: This error is returned if a register holds a value that cannot be decoded.
+    pub fn read_config(&mut self) -> Result<Config, Error<B::Error>> {
+        let mut buff = [0u8; 3];
+        self.read_from_register(Reg::CfgRegA as u8, &mut buff)?;
+
+        Config::from_registers(
+            CfgRegA::from_bits(buff[0]),
+            CfgRegB::from_bits(buff[1]),
+            CfgRegC::from_bits(buff[2]),
+        )
+    }
+
+    /// Reads every register of the device.
+    ///
+    /// This function burst-reads the hard-iron offset, `WHO_AM_I`, and the registers from
+    /// `CFG_REG_A` to `TEMP_OUT_H_REG`, in three bus transactions, and decodes them into a
+    /// `RegisterSnapshot`. Reading the registers has the usual side effects: a latched interrupt is
+    /// cleared by the read of `INT_SOURCE_REG`, and the data-ready flags by the read of the outputs.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<RegisterSnapshot, Error<B::Error>>`: Returns the content of the registers. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub fn dump_registers(&mut self) -> Result<RegisterSnapshot, Error<B::Error>> {
+        let mut offset = [0u8; 6];
+        self.read_from_register(Reg::OffsetXRegL as u8, &mut offset)?;
+        let who_am_i = self.device_id_get()?;
+        let mut block = [0u8; SNAPSHOT_BLOCK_LEN];
+        self.read_from_register(Reg::CfgRegA as u8, &mut block)?;
+
+        Ok(RegisterSnapshot::from_registers(offset, who_am_i, block))
+    }
+
+    /// Writes back the writable registers of a snapshot.
+    ///
+    /// This function writes the hard-iron offset, the interrupt threshold, the registers from
+    /// `CFG_REG_B` to `INT_CTRL_REG`, then `CFG_REG_A` in a separate final write, so that the
+    /// operating mode is restored last. The `soft_rst` and `reboot` commands are not restored.
+    ///
+    /// # Arguments
+    ///
+    /// * `val`: The snapshot returned by `dump_registers`.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error<B::Error>>`: Returns `Ok(())` if the registers are successfully written. Returns an `Err` if the operation fails.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::Bus(B::Error)`: This error is returned if there is a failure in the bus operation.
+    pub fn restore_registers(&mut self, val: &RegisterSnapshot) -> Result<(), Error<B::Error>> {
+        let (offset, config, threshold) = val.to_registers();
+        self.write_to_register(Reg::OffsetXRegL as u8, &offset)?;
+        self.write_to_register(Reg::IntThsLReg as u8, &threshold)?;
+        self.write_to_register(Reg::CfgRegB as u8, &config[1..])?;
+        self.write_to_register(Reg::CfgRegA as u8, &config[..1])
     }
 }
 
@@ -925,14 +1819,73 @@ pub fn from_lsb_to_mgauss(lsb: i16) -> f32 {
     lsb as f32 * 1.5
 }
 
+/// Converts milligauss to LSB, rounding to the nearest value and saturating to the `i16` range.
+pub fn from_mgauss_to_lsb(mgauss: f32) -> i16 {
+    libm::roundf(mgauss / 1.5).clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
 /// Converts LSB to Celsius.
 pub fn from_lsb_to_celsius(lsb: i16) -> f32 {
     (lsb as f32 / 8.0) + 25.0
 }
 
+/// Converts LSB value to microgauss, using integer math only (1 LSB = 1500 uG).
+pub fn from_lsb_to_ugauss(lsb: i16) -> i32 {
+    lsb as i32 * 1500
+}
+
+/// Converts LSB value to nanotesla, using integer math only (1 LSB = 150 nT).
+pub fn from_lsb_to_ntesla(lsb: i16) -> i32 {
+    lsb as i32 * 150
+}
+
+/// Converts LSB to milli-degrees Celsius, using integer math only (1 LSB = 125 m°C).
+pub fn from_lsb_to_mcelsius(lsb: i16) -> i32 {
+    lsb as i32 * 125 + 25_000
+}
+
+/// Converts a raw X, Y, Z sample to microgauss, using integer math only.
+pub fn from_lsb_to_ugauss_xyz(raw: [i16; 3]) -> [i32; 3] {
+    raw.map(from_lsb_to_ugauss)
+}
+
+/// Converts a raw X, Y, Z sample to nanotesla, using integer math only.
+pub fn from_lsb_to_ntesla_xyz(raw: [i16; 3]) -> [i32; 3] {
+    raw.map(from_lsb_to_ntesla)
+}
+
+/// Returns the sample period for an output data rate, in milliseconds.
+pub(crate) fn odr_period_ms(odr: Odr) -> u32 {
+    match odr {
+        Odr::_10hz => 100,
+        Odr::_20hz => 50,
+        Odr::_50hz => 20,
+        Odr::_100hz => 10,
+    }
+}
+
+/// Maximum number of `soft_rst`/`reboot` polls performed by `init` before giving up.
+pub(crate) const INIT_MAX_RETRIES: u32 = 10;
+/// Delay between two consecutive `soft_rst`/`reboot` polls, in milliseconds.
+pub(crate) const INIT_POLL_DELAY_MS: u32 = 1;
+/// Time needed to reload the memory content after a reboot, in milliseconds.
+pub(crate) const BOOT_TIME_MS: u32 = 20;
+/// Time needed to get stable output after power up, in milliseconds.
+pub(crate) const POWER_UP_TIME_MS: u32 = 20;
+
+/// Delay between two consecutive `STATUS_REG` polls while waiting for new data, in milliseconds.
+pub(crate) const DRDY_POLL_DELAY_MS: u32 = 1;
+/// Maximum time to wait for a single self-test sample at 100 Hz, in milliseconds.
+pub(crate) const SELF_TEST_DRDY_TIMEOUT_MS: u32 = 50;
+
+/// Maximum time to wait for a single measurement, offset cancellation included, in milliseconds.
+pub(crate) const SINGLE_MEASUREMENT_TIMEOUT_MS: u32 = 100;
+
 /// Represents the I2C address for the device.
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 pub enum I2CAddress {
     /// The I2C address for the device, set to `0x1e`.
     I2cAdd = 0x1e,
@@ -0,0 +1,60 @@
+use crate::from_lsb_to_mgauss;
+
+/// Magnetic field sample expressed in physical units.
+///
+/// Each axis holds the field strength in milligauss, converted from the raw output registers with
+/// the 1.5 mG/LSB sensitivity of the sensor.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct MagneticField {
+    /// X-axis field in milligauss.
+    pub x: f32,
+    /// Y-axis field in milligauss.
+    pub y: f32,
+    /// Z-axis field in milligauss.
+    pub z: f32,
+}
+
+impl MagneticField {
+    /// Builds a magnetic field sample from the raw output values.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw`: The raw X, Y, Z values, as returned by `magnetic_raw_get`.
+    pub fn from_raw(raw: [i16; 3]) -> Self {
+        Self {
+            x: from_lsb_to_mgauss(raw[0]),
+            y: from_lsb_to_mgauss(raw[1]),
+            z: from_lsb_to_mgauss(raw[2]),
+        }
+    }
+
+    /// Returns the X, Y, Z field in milligauss as an array.
+    pub fn to_array(&self) -> [f32; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    /// Returns the X, Y, Z field in gauss (1 G = 1000 mG).
+    #[cfg(feature = "units")]
+    pub fn to_gauss(&self) -> [f32; 3] {
+        self.to_array().map(|v| v / 1000.0)
+    }
+
+    /// Returns the X, Y, Z field in microtesla (1 mG = 0.1 uT).
+    #[cfg(feature = "units")]
+    pub fn to_microtesla(&self) -> [f32; 3] {
+        self.to_array().map(|v| v / 10.0)
+    }
+
+    /// Returns the X, Y, Z field in nanotesla (1 mG = 100 nT).
+    #[cfg(feature = "units")]
+    pub fn to_nanotesla(&self) -> [f32; 3] {
+        self.to_array().map(|v| v * 100.0)
+    }
+}
+
+impl From<[i16; 3]> for MagneticField {
+    fn from(raw: [i16; 3]) -> Self {
+        Self::from_raw(raw)
+    }
+}
@@ -8,7 +8,9 @@ use st_mem_bank_macro::{named_register, register};
 
 /// Represents the register addresses for device configuration and data retrieval.
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 pub enum Reg {
     /// Address for the low byte of the X-axis offset register.
     OffsetXRegL = 0x45,
@@ -66,8 +68,8 @@ pub enum Reg {
 /// * `Msb`: Most significant bit first.
 /// * `Lsb`: Least significant bit first (default).
 #[register(address = Reg::CfgRegA, access_type = Iis2mdc, generics = 2)]
-#[cfg_attr(feature = "bit_order_msb", bitfield(u8, order = Msb))]
-#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u8, order = Lsb))]
+#[cfg_attr(feature = "bit_order_msb", bitfield(u8, order = Msb, defmt = cfg(feature = "defmt")))]
+#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u8, order = Lsb, defmt = cfg(feature = "defmt")))]
 pub struct CfgRegA {
     /// This bits select the mode of operation of the device.
     #[bits(2, default = 0b11)]
@@ -98,8 +100,8 @@ pub struct CfgRegA {
 /// * `Msb`: Most significant bit first.
 /// * `Lsb`: Least significant bit first (default).
 #[register(address = Reg::CfgRegB, access_type = Iis2mdc, generics = 2)]
-#[cfg_attr(feature = "bit_order_msb", bitfield(u8, order = Msb))]
-#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u8, order = Lsb))]
+#[cfg_attr(feature = "bit_order_msb", bitfield(u8, order = Msb, defmt = cfg(feature = "defmt")))]
+#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u8, order = Lsb, defmt = cfg(feature = "defmt")))]
 pub struct CfgRegB {
     /// Enables low-pass filter.
     #[bits(1)]
@@ -129,8 +131,8 @@ pub struct CfgRegB {
 /// * `Msb`: Most significant bit first.
 /// * `Lsb`: Least significant bit first (default).
 #[register(address = Reg::CfgRegC, access_type = Iis2mdc, generics = 2)]
-#[cfg_attr(feature = "bit_order_msb", bitfield(u8, order = Msb))]
-#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u8, order = Lsb))]
+#[cfg_attr(feature = "bit_order_msb", bitfield(u8, order = Msb, defmt = cfg(feature = "defmt")))]
+#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u8, order = Lsb, defmt = cfg(feature = "defmt")))]
 pub struct CfgRegC {
     /// If `1`, the data-ready signal is driven on the INT/DRDY pin. The INT/DRDY pin is configured
     /// in push-pull output mode.
@@ -170,8 +172,8 @@ pub struct CfgRegC {
 /// * `Msb`: Most significant bit first.
 /// * `Lsb`: Least significant bit first (default).
 #[register(address = Reg::IntCtrlReg, access_type = Iis2mdc, generics = 2)]
-#[cfg_attr(feature = "bit_order_msb", bitfield(u8, order = Msb))]
-#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u8, order = Lsb))]
+#[cfg_attr(feature = "bit_order_msb", bitfield(u8, order = Msb, defmt = cfg(feature = "defmt")))]
+#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u8, order = Lsb, defmt = cfg(feature = "defmt")))]
 pub struct IntCtrlReg {
     /// Interrupt enable. When set, enables the interrupt generation.
     #[bits(1)]
@@ -205,8 +207,8 @@ pub struct IntCtrlReg {
 /// * `Msb`: Most significant bit first.
 /// * `Lsb`: Least significant bit first (default).
 #[register(address = Reg::IntSourceReg, access_type = Iis2mdc, generics = 2)]
-#[cfg_attr(feature = "bit_order_msb", bitfield(u8, order = Msb))]
-#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u8, order = Lsb))]
+#[cfg_attr(feature = "bit_order_msb", bitfield(u8, order = Msb, defmt = cfg(feature = "defmt")))]
+#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u8, order = Lsb, defmt = cfg(feature = "defmt")))]
 pub struct IntSourceReg {
     /// This bit signals when the interrupt event occurs.
     #[bits(1, access = RO)]
@@ -242,8 +244,8 @@ pub struct IntSourceReg {
 /// * `Msb`: Most significant bit first.
 /// * `Lsb`: Least significant bit first (default).
 #[register(address = Reg::StatusReg, access_type = Iis2mdc, generics = 2)]
-#[cfg_attr(feature = "bit_order_msb", bitfield(u8, order = Msb))]
-#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u8, order = Lsb))]
+#[cfg_attr(feature = "bit_order_msb", bitfield(u8, order = Msb, defmt = cfg(feature = "defmt")))]
+#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u8, order = Lsb, defmt = cfg(feature = "defmt")))]
 pub struct StatusReg {
     /// X-axis new data available.
     #[bits(1, access = RO)]
@@ -276,6 +278,9 @@ pub struct StatusReg {
 /// This register group holds the hard-iron offset values used to compensate the magnetic sensor readings.
 /// The offsets are represented as a three-element array of 16-bit signed integers, corresponding to the X, Y, and Z axes respectively.
 #[named_register(address = Reg::OffsetXRegL, access_type = Iis2mdc, generics = 2)]
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 pub struct OffsetXYZ {
     pub x: i16,
     pub y: i16,
@@ -287,6 +292,9 @@ pub struct OffsetXYZ {
 /// This register group contains the raw magnetic output data from the sensor.
 /// The data is represented as a three-element array of 16-bit signed integers, corresponding to the X, Y, and Z axes respectively.
 #[named_register(address = Reg::OutxLReg, access_type = Iis2mdc, generics = 2)]
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 pub struct OutXYZ {
     pub x: i16,
     pub y: i16,
@@ -299,8 +307,8 @@ pub struct OutXYZ {
 /// The bit order can be configured via the `bit_order_msb` feature flag.
 /// The temperature value is accessible through the `temp_out` field.
 #[register(address = Reg::TempOutLReg, access_type = Iis2mdc, generics = 2)]
-#[cfg_attr(feature = "bit_order_msb", bitfield(u16, order = Msb))]
-#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u16, order = Lsb))]
+#[cfg_attr(feature = "bit_order_msb", bitfield(u16, order = Msb, defmt = cfg(feature = "defmt")))]
+#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u16, order = Lsb, defmt = cfg(feature = "defmt")))]
 pub struct TempOutReg {
     /// Raw temperature output value (16 bits, read-only, signed).
     #[bits(16, access = RO)]
@@ -313,17 +321,50 @@ pub struct TempOutReg {
 /// The threshold is represented as a 16-bit signed integer.
 /// The bit order can be configured via the `bit_order_msb` feature flag.
 #[register(address = Reg::IntThsLReg, access_type = Iis2mdc, generics = 2)]
-#[cfg_attr(feature = "bit_order_msb", bitfield(u16, order = Msb))]
-#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u16, order = Lsb))]
+#[cfg_attr(feature = "bit_order_msb", bitfield(u16, order = Msb, defmt = cfg(feature = "defmt")))]
+#[cfg_attr(not(feature = "bit_order_msb"), bitfield(u16, order = Lsb, defmt = cfg(feature = "defmt")))]
 pub struct IntThsReg {
     /// Interrupt threshold value (16 bits, signed).
     #[bits(16)]
     pub int_ths: i16,
 }
 
+// bitfield-struct only generates Debug and defmt::Format, so the decoded fields are listed here.
+#[cfg(feature = "ufmt")]
+macro_rules! register_udebug {
+    ($($reg:ident { $($field:ident),* $(,)? })*) => {
+        $(
+            impl ufmt::uDebug for $reg {
+                fn fmt<W: ufmt::uWrite + ?Sized>(
+                    &self,
+                    f: &mut ufmt::Formatter<'_, W>,
+                ) -> Result<(), W::Error> {
+                    f.debug_struct(stringify!($reg))?
+                        $(.field(stringify!($field), &self.$field())?)*
+                        .finish()
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "ufmt")]
+register_udebug! {
+    CfgRegA { md, odr, lp, soft_rst, reboot, comp_temp_en }
+    CfgRegB { lpf, set_rst, int_on_dataoff, off_canc_one_shot }
+    CfgRegC { drdy_on_pin, self_test, ble, bdu, i2c_dis, int_on_pin }
+    IntCtrlReg { ien, iel, iea, zien, yien, xien }
+    IntSourceReg { int, mroi, n_th_s_z, n_th_s_y, n_th_s_x, p_th_s_z, p_th_s_y, p_th_s_x }
+    StatusReg { xda, yda, zda, zyxda, xor, yor, zor, zyxor }
+    TempOutReg { temp_out }
+    IntThsReg { int_ths }
+}
+
 /// Operating modes for the sensor.
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Default, TryFrom)]
+#[derive(Clone, Copy, PartialEq, Default, Debug, TryFrom)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 #[try_from(repr)]
 pub enum Md {
     /// Continuous mode.
@@ -333,11 +374,15 @@ pub enum Md {
     SingleTrigger = 1,
     /// Power down mode.
     PowerDown = 2,
+    /// Idle mode. This is the power-on state of the device and behaves as `PowerDown`.
+    Idle = 3,
 }
 
 /// Output data rates for the sensor.
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Default, TryFrom)]
+#[derive(Clone, Copy, PartialEq, Default, Debug, TryFrom)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 #[try_from(repr)]
 pub enum Odr {
     /// Output data rate of 10 Hz.
@@ -353,7 +398,9 @@ pub enum Odr {
 
 /// Power modes for the sensor.
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Default, TryFrom)]
+#[derive(Clone, Copy, PartialEq, Default, Debug, TryFrom)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 #[try_from(repr)]
 pub enum Lp {
     /// High-resolution mode.
@@ -365,7 +412,9 @@ pub enum Lp {
 
 /// Low-pass filter bandwidth for the sensor.
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Default, TryFrom)]
+#[derive(Clone, Copy, PartialEq, Default, Debug, TryFrom)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 #[try_from(repr)]
 pub enum Lpf {
     /// Low-pass filter bandwidth of ODR/2
@@ -377,7 +426,9 @@ pub enum Lpf {
 
 /// Reset pulse mode for the sensor.
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Default, TryFrom)]
+#[derive(Clone, Copy, PartialEq, Default, Debug, TryFrom)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 #[try_from(repr)]
 pub enum SetRst {
     /// Set/reset sensor every ODR/63.
@@ -387,11 +438,15 @@ pub enum SetRst {
     SensOffCancEveryOdr = 1,
     /// Set/reset sensor only at power on.
     SetSensOnlyAtPowerOn = 2,
+    /// Offset cancellation enabled, set/reset sensor only at power on.
+    SensOffCancOnlyAtPowerOn = 3,
 }
 
 /// Data format options for the sensor (Big/Little Endian).
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Default, TryFrom)]
+#[derive(Clone, Copy, PartialEq, Default, Debug, TryFrom)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 #[try_from(repr)]
 pub enum Ble {
     /// Least significant byte at lower address.
@@ -403,7 +458,9 @@ pub enum Ble {
 
 /// Interrupt configuration options for data checks.
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Default, TryFrom)]
+#[derive(Clone, Copy, PartialEq, Default, Debug, TryFrom)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 #[try_from(repr)]
 pub enum IntOnDataOff {
     /// Check data before hard-iron correction.
@@ -413,9 +470,39 @@ pub enum IntOnDataOff {
     CheckAfter = 1,
 }
 
+/// Interrupt latch options for the `iel` bit of `IntCtrlReg`.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Default, Debug, TryFrom)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+#[try_from(repr)]
+pub enum Iel {
+    /// The interrupt signal is pulsed.
+    #[default]
+    Pulsed = 0,
+    /// The interrupt signal is latched until `IntSourceReg` is read.
+    Latched = 1,
+}
+
+/// Interrupt polarity options for the `iea` bit of `IntCtrlReg`.
+#[repr(u8)]
+#[derive(Clone, Copy, PartialEq, Default, Debug, TryFrom)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+#[try_from(repr)]
+pub enum Iea {
+    /// The interrupt signal is active low.
+    #[default]
+    ActiveLow = 0,
+    /// The interrupt signal is active high.
+    ActiveHigh = 1,
+}
+
 /// I2C interface enable/disable options.
 #[repr(u8)]
-#[derive(Clone, Copy, PartialEq, Default, TryFrom)]
+#[derive(Clone, Copy, PartialEq, Default, Debug, TryFrom)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 #[try_from(repr)]
 pub enum I2cDis {
     /// I2C interface enabled.
@@ -0,0 +1,69 @@
+use crate::Error;
+
+/// Number of samples averaged with self-test disabled and enabled.
+pub const SELF_TEST_SAMPLES: usize = 50;
+/// Minimum absolute self-test deviation, in milligauss.
+pub const SELF_TEST_MIN_MG: f32 = 15.0;
+/// Maximum absolute self-test deviation, in milligauss.
+pub const SELF_TEST_MAX_MG: f32 = 500.0;
+
+/// Time to wait after enabling the self-test before collecting samples, in milliseconds.
+pub(crate) const SELF_TEST_SETTLING_MS: u32 = 60;
+
+/// Per-axis result of the self-test procedure.
+///
+/// All the values are expressed in milligauss and ordered as X, Y, Z.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SelfTestReport {
+    /// Average of the samples collected with the self-test disabled.
+    pub baseline: [f32; 3],
+    /// Average of the samples collected with the self-test enabled.
+    pub self_test: [f32; 3],
+    /// Absolute difference between `self_test` and `baseline`.
+    pub delta: [f32; 3],
+    /// `true` if `delta` is within [`SELF_TEST_MIN_MG`]..=[`SELF_TEST_MAX_MG`].
+    pub passed: [bool; 3],
+}
+
+impl SelfTestReport {
+    /// Builds the report from the averaged samples, evaluating each axis against the limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `baseline`: Average of the samples with the self-test disabled, in milligauss.
+    /// * `self_test`: Average of the samples with the self-test enabled, in milligauss.
+    pub fn new(baseline: [f32; 3], self_test: [f32; 3]) -> Self {
+        let mut delta = [0.0; 3];
+        let mut passed = [false; 3];
+        for axis in 0..3 {
+            delta[axis] = (self_test[axis] - baseline[axis]).abs();
+            passed[axis] = (SELF_TEST_MIN_MG..=SELF_TEST_MAX_MG).contains(&delta[axis]);
+        }
+
+        Self {
+            baseline,
+            self_test,
+            delta,
+            passed,
+        }
+    }
+
+    /// Returns `true` if all the axes passed the self-test.
+    pub fn is_passed(&self) -> bool {
+        self.passed.iter().all(|&passed| passed)
+    }
+
+    /// Converts the report into a `Result`.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::SelfTestFailed`: This error is returned if at least one axis failed.
+    pub fn check<E>(&self) -> Result<(), Error<E>> {
+        if self.is_passed() {
+            Ok(())
+        } else {
+            Err(Error::SelfTestFailed)
+        }
+    }
+}
@@ -0,0 +1,371 @@
+//! Access to the IIS2MDC through the sensor hub of an ST IMU.
+//!
+//! Many ST IMUs (LSM6DSO, LSM6DSOX, LSM6DSV, ...) embed an I2C master, the sensor hub, that drives
+//! up to four external sensors on the auxiliary I2C bus. When the magnetometer is only wired to that
+//! bus, every register access has to be relayed by the IMU: the slave address and register are
+//! programmed in the slave 0 configuration registers, the transaction is triggered, and read data
+//! are collected from the `SENSOR_HUB_x` output registers.
+//!
+//! [`SensorHubMaster`] abstracts this relay for a given IMU, and [`SensorHubBus`] turns any
+//! implementation into a `BusOperation`, so that the regular driver can be used on top of it:
+//!
+//! ```
+//! # use embedded_hal::delay::DelayNs;
+//! # fn example(imu_bus: impl st_mems_bus::BusOperation, imu_delay: impl DelayNs, delay: impl DelayNs) {
+//! use iis2mdc_rs::Iis2mdc;
+//! use iis2mdc_rs::sensor_hub::Lsm6dsoSensorHub;
+//!
+//! // `imu_bus` is the st_mems_bus bus of the LSM6DSO, `imu_delay` its timer
+//! let master = Lsm6dsoSensorHub::new(imu_bus, imu_delay);
+//! let mut sensor = Iis2mdc::new_sensor_hub(master, delay);
+//! let whoami = sensor.device_id_get().unwrap();
+//! # }
+//! ```
+//!
+//! [`Lsm6dsoSensorHub`] implements the relay with the LSM6DSO register map, which is shared by the
+//! LSM6DSOX and LSM6DSO32. Other masters only need to implement the two methods of
+//! [`SensorHubMaster`].
+
+use core::fmt::Debug;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::i2c::SevenBitAddress;
+use st_mems_bus::BusOperation;
+
+use crate::I2CAddress;
+
+/// Maximum time allowed for one sensor-hub transaction, in milliseconds.
+///
+/// The LSM6DSO runs one transaction per accelerometer sample, i.e. about every 10 ms at 104 Hz.
+pub const SENSOR_HUB_TIMEOUT_MS: u32 = 50;
+
+/// I2C master of an IMU relaying register accesses to an external sensor.
+pub trait SensorHubMaster {
+    type Error: Debug;
+
+    /// Maximum number of bytes read in a single transaction.
+    ///
+    /// Longer reads are split by [`SensorHubBus`] into consecutive transactions.
+    const MAX_READ_LEN: usize;
+
+    /// Writes one byte to a register of the external sensor.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: The 7-bit I2C address of the external sensor.
+    /// * `reg`: The register to write.
+    /// * `data`: The value to write.
+    ///
+    /// # Returns
+    ///
+    /// * `Result`
+    ///     * `()`
+    ///     * `Err`: Returns an error if the transaction fails.
+    fn slave_write(
+        &mut self,
+        address: SevenBitAddress,
+        reg: u8,
+        data: u8,
+    ) -> Result<(), Self::Error>;
+
+    /// Reads consecutive registers of the external sensor.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: The 7-bit I2C address of the external sensor.
+    /// * `reg`: The first register to read.
+    /// * `buf`: The buffer to fill, at most [`Self::MAX_READ_LEN`] bytes long.
+    ///
+    /// # Returns
+    ///
+    /// * `Result`
+    ///     * `()`
+    ///     * `Err`: Returns an error if the transaction fails.
+    fn slave_read(
+        &mut self,
+        address: SevenBitAddress,
+        reg: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Self::Error>;
+}
+
+/// `BusOperation` relaying the register accesses through a [`SensorHubMaster`].
+///
+/// Writes are performed one byte at a time, to consecutive registers; reads longer than
+/// [`SensorHubMaster::MAX_READ_LEN`] are split into several transactions, so a burst read is not
+/// guaranteed to be coherent unless block data update is enabled.
+pub struct SensorHubBus<M> {
+    /// The sensor-hub master.
+    pub master: M,
+    /// The 7-bit I2C address of the IIS2MDC on the auxiliary bus.
+    pub address: SevenBitAddress,
+    pointer: u8,
+}
+
+impl<M: SensorHubMaster> SensorHubBus<M> {
+    /// Creates a new sensor-hub bus.
+    ///
+    /// # Arguments
+    ///
+    /// * `master`: The sensor-hub master.
+    /// * `address`: The I2C address of the IIS2MDC on the auxiliary bus.
+    pub fn new(master: M, address: I2CAddress) -> Self {
+        Self {
+            master,
+            address: address as SevenBitAddress,
+            pointer: 0,
+        }
+    }
+}
+
+impl<M: SensorHubMaster> BusOperation for SensorHubBus<M> {
+    type Error = M::Error;
+
+    /// Reads from the register following the last one accessed.
+    fn read_bytes(&mut self, rbuf: &mut [u8]) -> Result<(), Self::Error> {
+        let mut reg = self.pointer;
+        for chunk in rbuf.chunks_mut(M::MAX_READ_LEN) {
+            self.master.slave_read(self.address, reg, chunk)?;
+            reg = reg.wrapping_add(chunk.len() as u8);
+        }
+        self.pointer = reg;
+        Ok(())
+    }
+
+    fn write_bytes(&mut self, wbuf: &[u8]) -> Result<(), Self::Error> {
+        let Some((&reg, data)) = wbuf.split_first() else {
+            return Ok(());
+        };
+        self.pointer = reg;
+        for &byte in data {
+            self.master.slave_write(self.address, self.pointer, byte)?;
+            self.pointer = self.pointer.wrapping_add(1);
+        }
+        Ok(())
+    }
+
+    fn write_byte_read_bytes(
+        &mut self,
+        wbuf: &[u8; 1],
+        rbuf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.pointer = wbuf[0];
+        self.read_bytes(rbuf)
+    }
+}
+
+/// Errors returned by [`Lsm6dsoSensorHub`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum SensorHubError<E> {
+    /// An error occurred on the bus of the IMU.
+    Bus(E),
+    /// The external sensor did not acknowledge the transaction.
+    Nack,
+    /// The sensor hub did not complete the transaction within [`SENSOR_HUB_TIMEOUT_MS`].
+    Timeout,
+}
+
+impl<E> From<E> for SensorHubError<E> {
+    fn from(err: E) -> Self {
+        SensorHubError::Bus(err)
+    }
+}
+
+impl<E: Debug> core::fmt::Display for SensorHubError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SensorHubError::Bus(err) => write!(f, "sensor hub bus error: {err:?}"),
+            SensorHubError::Nack => write!(f, "external sensor did not acknowledge"),
+            SensorHubError::Timeout => write!(f, "timeout while waiting for the sensor hub"),
+        }
+    }
+}
+
+impl<E: Debug> core::error::Error for SensorHubError<E> {}
+
+// LSM6DSO user bank
+const FUNC_CFG_ACCESS: u8 = 0x01;
+const CTRL1_XL: u8 = 0x10;
+const STATUS_REG: u8 = 0x1E;
+const OUTX_L_A: u8 = 0x28;
+const STATUS_MASTER_MAINPAGE: u8 = 0x39;
+
+// LSM6DSO sensor hub bank
+const SENSOR_HUB_1: u8 = 0x02;
+const MASTER_CONFIG: u8 = 0x14;
+const SLV0_ADD: u8 = 0x15;
+const DATAWRITE_SLV0: u8 = 0x21;
+
+const SHUB_REG_ACCESS: u8 = 0x40;
+const ODR_XL_MASK: u8 = 0xF0;
+const ODR_XL_104HZ: u8 = 0x40;
+const MASTER_ON: u8 = 0x04;
+const SHUB_PU_EN: u8 = 0x08;
+const WRITE_ONCE: u8 = 0x40;
+const XLDA: u8 = 0x01;
+const SENS_HUB_ENDOP: u8 = 0x01;
+const SLAVE0_NACK: u8 = 0x08;
+const SLV0_NUMOP_MASK: u8 = 0x07;
+
+/// [`SensorHubMaster`] for the LSM6DSO family, using slave 0.
+///
+/// Each transaction programs slave 0, turns the I2C master on and runs the accelerometer at 104 Hz
+/// to trigger it, waits for a new accelerometer sample and `SENS_HUB_ENDOP`, then turns the master
+/// off and restores the previous accelerometer configuration, also when the transaction fails.
+/// Slaves 1 to 3 are not used.
+pub struct Lsm6dsoSensorHub<B, T> {
+    /// The bus of the IMU.
+    pub bus: B,
+    pub tim: T,
+    /// Enables the internal pull-up resistors of the auxiliary I2C bus (`SHUB_PU_EN`).
+    pub pull_up: u8,
+}
+
+impl<B: BusOperation, T: DelayNs> Lsm6dsoSensorHub<B, T> {
+    /// Creates a new LSM6DSO sensor-hub master, with the internal pull-ups disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `bus`: The bus of the IMU.
+    /// * `tim`: The timer used to poll the end of the transactions.
+    pub fn new(bus: B, tim: T) -> Self {
+        Self {
+            bus,
+            tim,
+            pull_up: 0,
+        }
+    }
+
+    fn write_reg(&mut self, reg: u8, data: &[u8]) -> Result<(), SensorHubError<B::Error>> {
+        Ok(self.bus.write_to_register(reg, data)?)
+    }
+
+    fn read_reg(&mut self, reg: u8, buf: &mut [u8]) -> Result<(), SensorHubError<B::Error>> {
+        Ok(self.bus.read_from_register(reg, buf)?)
+    }
+
+    /// Programs slave 0 and runs one sensor-hub cycle.
+    ///
+    /// `slave` holds `SLV0_ADD`, `SLV0_SUBADD` and `SLV0_CONFIG`. Once the accelerometer
+    /// configuration is read, the I2C master and the accelerometer are restored on both the
+    /// success and error paths, and the first error is returned.
+    fn transaction(
+        &mut self,
+        slave: &[u8; 3],
+        data: Option<u8>,
+    ) -> Result<(), SensorHubError<B::Error>> {
+        let mut ctrl1_xl = [0];
+        self.read_reg(CTRL1_XL, &mut ctrl1_xl)?;
+        let ctrl1_xl = ctrl1_xl[0];
+
+        let mut master_config = MASTER_ON;
+        if self.pull_up == 1 {
+            master_config |= SHUB_PU_EN;
+        }
+        if data.is_some() {
+            master_config |= WRITE_ONCE;
+        }
+
+        let result = self.run_cycle(ctrl1_xl, slave, data, master_config);
+        let restored = self.restore(ctrl1_xl, master_config);
+
+        result.and(restored)
+    }
+
+    fn run_cycle(
+        &mut self,
+        ctrl1_xl: u8,
+        slave: &[u8; 3],
+        data: Option<u8>,
+        master_config: u8,
+    ) -> Result<(), SensorHubError<B::Error>> {
+        self.write_reg(CTRL1_XL, &[ctrl1_xl & !ODR_XL_MASK])?;
+
+        self.write_reg(FUNC_CFG_ACCESS, &[SHUB_REG_ACCESS])?;
+        self.write_reg(SLV0_ADD, slave)?;
+        if let Some(data) = data {
+            self.write_reg(DATAWRITE_SLV0, &[data])?;
+        }
+        self.write_reg(MASTER_CONFIG, &[master_config])?;
+        self.write_reg(FUNC_CFG_ACCESS, &[0])?;
+
+        // Discard any pending accelerometer sample, then trigger the sensor hub
+        let mut accel = [0; 6];
+        self.read_reg(OUTX_L_A, &mut accel)?;
+        self.write_reg(CTRL1_XL, &[(ctrl1_xl & !ODR_XL_MASK) | ODR_XL_104HZ])?;
+
+        self.wait_end_of_operation()
+    }
+
+    /// Turns the I2C master off, switches back to the user bank and restores the accelerometer,
+    /// attempting every step even if one fails.
+    fn restore(&mut self, ctrl1_xl: u8, master_config: u8) -> Result<(), SensorHubError<B::Error>> {
+        let bank = self.write_reg(FUNC_CFG_ACCESS, &[SHUB_REG_ACCESS]);
+        // MASTER_CONFIG is only reachable in the sensor hub bank
+        let master = match bank {
+            Ok(()) => self.write_reg(MASTER_CONFIG, &[master_config & SHUB_PU_EN]),
+            Err(_) => Ok(()),
+        };
+        let user = self.write_reg(FUNC_CFG_ACCESS, &[0]);
+        let accel = self.write_reg(CTRL1_XL, &[ctrl1_xl]);
+
+        bank.and(master).and(user).and(accel)
+    }
+
+    /// Waits for a new accelerometer sample, which triggers the sensor hub, then for the end of
+    /// the transaction.
+    fn wait_end_of_operation(&mut self) -> Result<(), SensorHubError<B::Error>> {
+        let mut triggered = false;
+        for _ in 0..=SENSOR_HUB_TIMEOUT_MS {
+            let mut status = [0];
+            if !triggered {
+                self.read_reg(STATUS_REG, &mut status)?;
+                triggered = status[0] & XLDA != 0;
+            }
+            if triggered {
+                self.read_reg(STATUS_MASTER_MAINPAGE, &mut status)?;
+                if status[0] & SLAVE0_NACK != 0 {
+                    return Err(SensorHubError::Nack);
+                }
+                if status[0] & SENS_HUB_ENDOP != 0 {
+                    return Ok(());
+                }
+            }
+            self.tim.delay_ms(1);
+        }
+
+        Err(SensorHubError::Timeout)
+    }
+}
+
+impl<B: BusOperation, T: DelayNs> SensorHubMaster for Lsm6dsoSensorHub<B, T> {
+    type Error = SensorHubError<B::Error>;
+
+    const MAX_READ_LEN: usize = SLV0_NUMOP_MASK as usize;
+
+    fn slave_write(
+        &mut self,
+        address: SevenBitAddress,
+        reg: u8,
+        data: u8,
+    ) -> Result<(), Self::Error> {
+        self.transaction(&[address << 1, reg, 0], Some(data))
+    }
+
+    fn slave_read(
+        &mut self,
+        address: SevenBitAddress,
+        reg: u8,
+        buf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let numop = buf.len().min(Self::MAX_READ_LEN) as u8;
+        self.transaction(&[(address << 1) | 1, reg, numop], None)?;
+
+        self.write_reg(FUNC_CFG_ACCESS, &[SHUB_REG_ACCESS])?;
+        let result = self.read_reg(SENSOR_HUB_1, &mut buf[..numop as usize]);
+        let user = self.write_reg(FUNC_CFG_ACCESS, &[0]);
+
+        result.and(user)
+    }
+}
@@ -0,0 +1,489 @@
+//! Simulated IIS2MDC for host-side tests.
+//!
+//! [`SimIis2mdc`] implements `st_mems_bus::BusOperation` on top of an in-memory copy of the
+//! register map (0x45 to 0x6F), so that the driver, and the code built on it, can be exercised with
+//! `cargo test` without hardware:
+//!
+//! ```
+//! use iis2mdc_rs::Iis2mdc;
+//! use iis2mdc_rs::sim::{NoopDelay, SimIis2mdc};
+//!
+//! let mut sim = SimIis2mdc::new();
+//! sim.set_field([100, -200, 300]);
+//! let mut sensor = Iis2mdc::from_bus(sim, NoopDelay);
+//! ```
+//!
+//! The simulator models the behavior the driver relies on:
+//!
+//! - read-only registers and bits ignore writes;
+//! - `soft_rst` restores the power-on values of the user registers and clears itself, `reboot`
+//!   clears itself;
+//! - in continuous or single mode, a new sample updates the output registers, sets the data-ready
+//!   flags of `STATUS_REG` (and the overrun flags if the previous sample was not read) and
+//!   evaluates the threshold interrupt. Single mode goes back to idle after one sample;
+//! - reading the output registers clears `STATUS_REG`;
+//! - reading `INT_SOURCE_REG` clears it when the interrupt is latched.
+//!
+//! New samples are produced with [`SimIis2mdc::generate`], or automatically when `STATUS_REG` is
+//! read without pending data (see [`SimIis2mdc::set_auto_generate`]). Their values come from the
+//! fields queued with [`SimIis2mdc::inject_field`], then from the last field set. The hard-iron
+//! offset is subtracted from the field, and a fixed deviation is added when the self-test is
+//! enabled.
+//!
+//! The registers are encoded and decoded with the register structs of this crate, so the
+//! simulator follows the `bit_order_msb` feature.
+
+use crate::prelude::*;
+use crate::{IIS2MDC_ID, from_mgauss_to_lsb};
+use embedded_hal::delay::DelayNs;
+use st_mems_bus::BusOperation;
+
+/// Maximum number of fields that can be queued with [`SimIis2mdc::inject_field`].
+pub const SIM_SCRIPT_CAPACITY: usize = 32;
+
+/// Deviation added to each axis while the self-test is enabled, in milligauss.
+pub const SIM_SELF_TEST_DELTA_MG: f32 = 150.0;
+
+const FIRST_ADDRESS: u8 = Reg::OffsetXRegL as u8;
+const LAST_ADDRESS: u8 = Reg::TempOutHReg as u8;
+const MAP_LEN: usize = (LAST_ADDRESS - FIRST_ADDRESS) as usize + 1;
+
+/// Errors returned by the simulated bus.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum SimError {
+    /// The transaction addressed a register outside 0x45 to 0x6F.
+    InvalidAddress(u8),
+    /// A write transaction did not contain the register address.
+    EmptyWrite,
+}
+
+/// Simulated IIS2MDC register map.
+#[derive(Clone, Debug)]
+pub struct SimIis2mdc {
+    regs: [u8; MAP_LEN],
+    address: u8,
+    field: [i16; 3],
+    temperature: i16,
+    script: [[i16; 3]; SIM_SCRIPT_CAPACITY],
+    script_head: usize,
+    script_len: usize,
+    auto_generate: bool,
+}
+
+impl Default for SimIis2mdc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimIis2mdc {
+    /// Creates a simulated device with the power-on register values, a zero field and a
+    /// temperature of 25 °C.
+    pub fn new() -> Self {
+        let mut sim = Self {
+            regs: [0; MAP_LEN],
+            address: FIRST_ADDRESS,
+            field: [0; 3],
+            temperature: 0,
+            script: [[0; 3]; SIM_SCRIPT_CAPACITY],
+            script_head: 0,
+            script_len: 0,
+            auto_generate: true,
+        };
+        sim.power_on();
+        sim
+    }
+
+    /// Restores the power-on values of all the registers.
+    pub fn power_on(&mut self) {
+        self.regs = [0; MAP_LEN];
+        self.poke(Reg::WhoAmI, IIS2MDC_ID);
+        self.reset_user_registers();
+    }
+
+    /// Returns the value of a register, without any side effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: The register address, from 0x45 to 0x6F.
+    pub fn register(&self, address: u8) -> Option<u8> {
+        Self::index(address).map(|i| self.regs[i])
+    }
+
+    /// Sets the value of a register, bypassing the read-only masks and side effects.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: The register address, from 0x45 to 0x6F.
+    /// * `val`: The new value.
+    pub fn set_register(&mut self, address: u8, val: u8) -> Result<(), SimError> {
+        let i = Self::index(address).ok_or(SimError::InvalidAddress(address))?;
+        self.regs[i] = val;
+        Ok(())
+    }
+
+    /// Sets the field returned once all the injected fields are consumed.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw`: The X, Y, Z field, in LSB (1 LSB = 1.5 mG), before hard-iron correction.
+    pub fn set_field(&mut self, raw: [i16; 3]) {
+        self.field = raw;
+    }
+
+    /// Queues a field for the next sample.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw`: The X, Y, Z field, in LSB (1 LSB = 1.5 mG), before hard-iron correction.
+    ///
+    /// # Returns
+    ///
+    /// * `bool`: Returns `false` if the queue is full and the field was not added.
+    pub fn inject_field(&mut self, raw: [i16; 3]) -> bool {
+        if self.script_len == SIM_SCRIPT_CAPACITY {
+            return false;
+        }
+        self.script[(self.script_head + self.script_len) % SIM_SCRIPT_CAPACITY] = raw;
+        self.script_len += 1;
+        true
+    }
+
+    /// Returns the number of injected fields not yet consumed.
+    pub fn pending_fields(&self) -> usize {
+        self.script_len
+    }
+
+    /// Sets the raw temperature of the next samples (8 LSB/°C, 0 LSB at 25 °C).
+    pub fn set_temperature(&mut self, raw: i16) {
+        self.temperature = raw;
+    }
+
+    /// Enables or disables the automatic generation of a sample when `STATUS_REG` is read without
+    /// pending data. Enabled by default, so that polling loops complete.
+    pub fn set_auto_generate(&mut self, enable: bool) {
+        self.auto_generate = enable;
+    }
+
+    /// Produces a new sample, as the device does once per output data period.
+    ///
+    /// # Returns
+    ///
+    /// * `bool`: Returns `false` if the device is in idle or power-down mode and no sample was produced.
+    pub fn generate(&mut self) -> bool {
+        let mut reg_a = CfgRegA::from_bits(self.peek(Reg::CfgRegA));
+        let md = reg_a.md();
+        if md != Md::ContinuousMode as u8 && md != Md::SingleTrigger as u8 {
+            return false;
+        }
+
+        let mut field = self.next_field();
+        if CfgRegC::from_bits(self.peek(Reg::CfgRegC)).self_test() == 1 {
+            let delta = from_mgauss_to_lsb(SIM_SELF_TEST_DELTA_MG);
+            field = field.map(|v| v.saturating_add(delta));
+        }
+        let offset = self.offset();
+        let out = [0, 1, 2].map(|axis| field[axis].saturating_sub(offset[axis]));
+
+        let bytes = OutXYZ {
+            x: out[0],
+            y: out[1],
+            z: out[2],
+        }
+        .to_le_bytes();
+        self.poke_bytes(Reg::OutxLReg, &bytes);
+        // The temperature field spans the whole register, whatever the bit order
+        self.poke_bytes(Reg::TempOutLReg, &self.temperature.to_le_bytes());
+
+        let mut status = mask(|bits| {
+            let reg = StatusReg::from_bits(bits);
+            reg.xda() | reg.yda() | reg.zda() | reg.zyxda()
+        });
+        if StatusReg::from_bits(self.peek(Reg::StatusReg)).zyxda() == 1 {
+            status |= mask(|bits| {
+                let reg = StatusReg::from_bits(bits);
+                reg.xor() | reg.yor() | reg.zor() | reg.zyxor()
+            });
+        }
+        self.poke(Reg::StatusReg, status);
+
+        self.update_interrupt(field, out);
+
+        if md == Md::SingleTrigger as u8 {
+            reg_a.set_md(Md::Idle as u8);
+            self.poke(Reg::CfgRegA, reg_a.into_bits());
+        }
+
+        true
+    }
+
+    /// Returns the level of the INT/DRDY pin.
+    ///
+    /// The pin is asserted by the data-ready signal when `drdy_on_pin` is enabled and by the
+    /// threshold interrupt when `int_on_pin` is enabled. When the interrupt is routed to the pin,
+    /// the `iea` bit sets the active level.
+    pub fn int_pin_level(&self) -> bool {
+        let reg_c = CfgRegC::from_bits(self.peek(Reg::CfgRegC));
+        let ctrl = IntCtrlReg::from_bits(self.peek(Reg::IntCtrlReg));
+        let drdy = reg_c.drdy_on_pin() == 1
+            && StatusReg::from_bits(self.peek(Reg::StatusReg)).zyxda() == 1;
+        let int = reg_c.int_on_pin() == 1
+            && IntSourceReg::from_bits(self.peek(Reg::IntSourceReg)).int() == 1;
+
+        let asserted = drdy || int;
+        if reg_c.int_on_pin() == 1 && ctrl.iea() == Iea::ActiveLow as u8 {
+            !asserted
+        } else {
+            asserted
+        }
+    }
+
+    fn index(address: u8) -> Option<usize> {
+        (FIRST_ADDRESS..=LAST_ADDRESS)
+            .contains(&address)
+            .then(|| (address - FIRST_ADDRESS) as usize)
+    }
+
+    fn peek(&self, reg: Reg) -> u8 {
+        self.regs[(reg as u8 - FIRST_ADDRESS) as usize]
+    }
+
+    fn poke(&mut self, reg: Reg, val: u8) {
+        self.regs[(reg as u8 - FIRST_ADDRESS) as usize] = val;
+    }
+
+    fn poke_bytes(&mut self, reg: Reg, bytes: &[u8]) {
+        let start = (reg as u8 - FIRST_ADDRESS) as usize;
+        self.regs[start..start + bytes.len()].copy_from_slice(bytes);
+    }
+
+    fn offset(&self) -> [i16; 3] {
+        let start = (Reg::OffsetXRegL as u8 - FIRST_ADDRESS) as usize;
+        let mut bytes = [0u8; 6];
+        bytes.copy_from_slice(&self.regs[start..start + 6]);
+        let offset = OffsetXYZ::from_le_bytes(bytes);
+        [offset.x, offset.y, offset.z]
+    }
+
+    fn next_field(&mut self) -> [i16; 3] {
+        if self.script_len > 0 {
+            self.field = self.script[self.script_head];
+            self.script_head = (self.script_head + 1) % SIM_SCRIPT_CAPACITY;
+            self.script_len -= 1;
+        }
+        self.field
+    }
+
+    fn reset_user_registers(&mut self) {
+        self.poke_bytes(Reg::OffsetXRegL, &[0; 6]);
+        self.poke(Reg::CfgRegA, CfgRegA::new().into_bits());
+        self.poke(Reg::CfgRegB, CfgRegB::new().into_bits());
+        self.poke(Reg::CfgRegC, CfgRegC::new().into_bits());
+        self.poke(Reg::IntCtrlReg, IntCtrlReg::new().into_bits());
+        self.poke(Reg::IntSourceReg, 0);
+        self.poke_bytes(Reg::IntThsLReg, &[0; 2]);
+    }
+
+    fn update_interrupt(&mut self, field: [i16; 3], out: [i16; 3]) {
+        let ctrl = IntCtrlReg::from_bits(self.peek(Reg::IntCtrlReg));
+        if ctrl.ien() == 0 {
+            return;
+        }
+
+        let ths = IntThsReg::from_bits(u16::from_le_bytes([
+            self.peek(Reg::IntThsLReg),
+            self.peek(Reg::IntThsHReg),
+        ]))
+        .int_ths();
+        let check_after = CfgRegB::from_bits(self.peek(Reg::CfgRegB)).int_on_dataoff()
+            == IntOnDataOff::CheckAfter as u8;
+        let value = if check_after { out } else { field };
+        let enabled = [ctrl.xien(), ctrl.yien(), ctrl.zien()].map(|en| en == 1);
+        let positive = [0, 1, 2].map(|axis| enabled[axis] && value[axis] > ths);
+        let negative = [0, 1, 2].map(|axis| enabled[axis] && value[axis] < ths.saturating_neg());
+
+        let mut source = mask(|bits| {
+            let reg = IntSourceReg::from_bits(bits);
+            let set = [reg.p_th_s_x(), reg.p_th_s_y(), reg.p_th_s_z()]
+                .iter()
+                .zip(positive)
+                .chain(
+                    [reg.n_th_s_x(), reg.n_th_s_y(), reg.n_th_s_z()]
+                        .iter()
+                        .zip(negative),
+                )
+                .any(|(&bit, flag)| bit == 1 && flag);
+            u8::from(set)
+        });
+        if source != 0 {
+            source |= mask(|bits| IntSourceReg::from_bits(bits).int());
+        }
+
+        // A latched interrupt keeps the flags until INT_SOURCE_REG is read
+        if ctrl.iel() == Iel::Latched as u8 {
+            source |= self.peek(Reg::IntSourceReg);
+        }
+        self.poke(Reg::IntSourceReg, source);
+    }
+
+    /// Writable bits of each register; read-only registers and bits are `0`.
+    fn write_mask(address: u8) -> u8 {
+        match address {
+            a if (Reg::OffsetXRegL as u8..=Reg::OffsetZRegH as u8).contains(&a) => 0xFF,
+            a if a == Reg::CfgRegA as u8 => 0xFF,
+            a if a == Reg::CfgRegB as u8 => CfgRegB::new()
+                .with_lpf(1)
+                .with_set_rst(0b11)
+                .with_int_on_dataoff(1)
+                .with_off_canc_one_shot(1)
+                .into_bits(),
+            a if a == Reg::CfgRegC as u8 => CfgRegC::new()
+                .with_drdy_on_pin(1)
+                .with_self_test(1)
+                .with_ble(1)
+                .with_bdu(1)
+                .with_i2c_dis(1)
+                .with_int_on_pin(1)
+                .into_bits(),
+            a if a == Reg::IntCtrlReg as u8 => IntCtrlReg::new()
+                .with_ien(1)
+                .with_iel(1)
+                .with_iea(1)
+                .with_zien(1)
+                .with_yien(1)
+                .with_xien(1)
+                .into_bits(),
+            a if a == Reg::IntThsLReg as u8 || a == Reg::IntThsHReg as u8 => 0xFF,
+            _ => 0x00,
+        }
+    }
+
+    fn write_register(&mut self, address: u8, val: u8) {
+        let i = (address - FIRST_ADDRESS) as usize;
+        let mask = Self::write_mask(address);
+        self.regs[i] = (self.regs[i] & !mask) | (val & mask);
+
+        if address == Reg::CfgRegA as u8 {
+            let mut reg_a = CfgRegA::from_bits(self.regs[i]);
+            if reg_a.soft_rst() == 1 {
+                self.reset_user_registers();
+                return;
+            }
+            reg_a.set_reboot(0);
+            self.regs[i] = reg_a.into_bits();
+        }
+    }
+
+    fn read_register(&mut self, address: u8) -> u8 {
+        if address == Reg::StatusReg as u8
+            && self.auto_generate
+            && StatusReg::from_bits(self.peek(Reg::StatusReg)).zyxda() == 0
+        {
+            self.generate();
+        }
+
+        let val = self.regs[(address - FIRST_ADDRESS) as usize];
+
+        if address == Reg::IntSourceReg as u8
+            && IntCtrlReg::from_bits(self.peek(Reg::IntCtrlReg)).iel() == Iel::Latched as u8
+        {
+            self.poke(Reg::IntSourceReg, 0);
+        }
+
+        val
+    }
+
+    fn read_burst(&mut self, rbuf: &mut [u8]) -> Result<(), SimError> {
+        let mut out_read = false;
+        for byte in rbuf.iter_mut() {
+            let address = self.address;
+            Self::index(address).ok_or(SimError::InvalidAddress(address))?;
+            *byte = self.read_register(address);
+            out_read |= (Reg::OutxLReg as u8..=Reg::OutzHReg as u8).contains(&address);
+            self.address = address.wrapping_add(1);
+        }
+
+        // Reading the output registers clears the data-ready and overrun flags
+        if out_read {
+            self.poke(Reg::StatusReg, 0);
+        }
+
+        Ok(())
+    }
+}
+
+impl BusOperation for SimIis2mdc {
+    type Error = SimError;
+
+    fn read_bytes(&mut self, rbuf: &mut [u8]) -> Result<(), Self::Error> {
+        self.read_burst(rbuf)
+    }
+
+    fn write_bytes(&mut self, wbuf: &[u8]) -> Result<(), Self::Error> {
+        let (&address, data) = wbuf.split_first().ok_or(SimError::EmptyWrite)?;
+        self.address = address;
+        for &val in data {
+            let address = self.address;
+            Self::index(address).ok_or(SimError::InvalidAddress(address))?;
+            self.write_register(address, val);
+            self.address = address.wrapping_add(1);
+        }
+        Ok(())
+    }
+
+    fn write_byte_read_bytes(
+        &mut self,
+        wbuf: &[u8; 1],
+        rbuf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.address = wbuf[0];
+        self.read_burst(rbuf)
+    }
+}
+
+#[cfg(feature = "async")]
+impl crate::asynchronous::BusOperation for SimIis2mdc {
+    type Error = SimError;
+
+    async fn read_bytes(&mut self, rbuf: &mut [u8]) -> Result<(), Self::Error> {
+        BusOperation::read_bytes(self, rbuf)
+    }
+
+    async fn write_bytes(&mut self, wbuf: &[u8]) -> Result<(), Self::Error> {
+        BusOperation::write_bytes(self, wbuf)
+    }
+
+    async fn write_byte_read_bytes(
+        &mut self,
+        wbuf: &[u8; 1],
+        rbuf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        BusOperation::write_byte_read_bytes(self, wbuf, rbuf)
+    }
+}
+
+/// Returns the bits of a register for which `field` reads a non-zero value.
+///
+/// Read-only fields have no setter, so their position, which depends on the bit order, is found
+/// by probing each bit.
+fn mask(field: impl Fn(u8) -> u8) -> u8 {
+    (0..8)
+        .map(|bit| 1u8 << bit)
+        .filter(|&bit| field(bit) != 0)
+        .fold(0, |acc, bit| acc | bit)
+}
+
+/// Delay that returns immediately, to run the driver against the simulator without waiting.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub struct NoopDelay;
+
+impl DelayNs for NoopDelay {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[cfg(feature = "async")]
+impl embedded_hal_async::delay::DelayNs for NoopDelay {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
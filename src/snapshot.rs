@@ -0,0 +1,99 @@
+use crate::prelude::*;
+
+/// Number of registers from `CFG_REG_A` (0x60) to `TEMP_OUT_H_REG` (0x6F).
+pub const SNAPSHOT_BLOCK_LEN: usize = 16;
+
+/// Content of every register of the device, read by `dump_registers`.
+///
+/// The `Debug` implementation prints the decoded fields of each register; use `{:#?}` for one field
+/// per line.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct RegisterSnapshot {
+    /// Hard-iron offset of the X, Y, Z axes (`OFFSET_X_REG_L` to `OFFSET_Z_REG_H`).
+    pub offset: [i16; 3],
+    /// Content of the `WHO_AM_I` register.
+    pub who_am_i: u8,
+    /// Content of the `CFG_REG_A` register.
+    pub cfg_reg_a: CfgRegA,
+    /// Content of the `CFG_REG_B` register.
+    pub cfg_reg_b: CfgRegB,
+    /// Content of the `CFG_REG_C` register.
+    pub cfg_reg_c: CfgRegC,
+    /// Content of the `INT_CTRL_REG` register.
+    pub int_ctrl_reg: IntCtrlReg,
+    /// Content of the `INT_SOURCE_REG` register.
+    pub int_source_reg: IntSourceReg,
+    /// Interrupt threshold (`INT_THS_L_REG` and `INT_THS_H_REG`).
+    pub int_threshold: i16,
+    /// Content of the `STATUS_REG` register.
+    pub status_reg: StatusReg,
+    /// Raw X, Y, Z magnetic output values (`OUTX_L_REG` to `OUTZ_H_REG`).
+    pub magnetic: [i16; 3],
+    /// Raw temperature output value (`TEMP_OUT_L_REG` and `TEMP_OUT_H_REG`).
+    pub temperature: i16,
+}
+
+impl RegisterSnapshot {
+    /// Decodes the register values read from the device.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset`: The values of the registers from `OFFSET_X_REG_L` (0x45) to `OFFSET_Z_REG_H` (0x4A).
+    /// * `who_am_i`: The value of the `WHO_AM_I` register (0x4F).
+    /// * `block`: The values of the registers from `CFG_REG_A` (0x60) to `TEMP_OUT_H_REG` (0x6F).
+    pub fn from_registers(offset: [u8; 6], who_am_i: u8, block: [u8; SNAPSHOT_BLOCK_LEN]) -> Self {
+        let offset = OffsetXYZ::from_le_bytes(offset);
+        let mut out = [0u8; 6];
+        out.copy_from_slice(&block[8..14]);
+        let out = OutXYZ::from_le_bytes(out);
+        let ths = IntThsReg::from_bits(u16::from_le_bytes([block[5], block[6]]));
+        let temp = TempOutReg::from_bits(u16::from_le_bytes([block[14], block[15]]));
+
+        Self {
+            offset: [offset.x, offset.y, offset.z],
+            who_am_i,
+            cfg_reg_a: CfgRegA::from_bits(block[0]),
+            cfg_reg_b: CfgRegB::from_bits(block[1]),
+            cfg_reg_c: CfgRegC::from_bits(block[2]),
+            int_ctrl_reg: IntCtrlReg::from_bits(block[3]),
+            int_source_reg: IntSourceReg::from_bits(block[4]),
+            int_threshold: ths.int_ths(),
+            status_reg: StatusReg::from_bits(block[7]),
+            magnetic: [out.x, out.y, out.z],
+            temperature: temp.temp_out(),
+        }
+    }
+
+    /// Encodes the writable registers.
+    ///
+    /// The `soft_rst` and `reboot` commands of `CFG_REG_A` are cleared, so that restoring a snapshot
+    /// taken during a reset does not reset the device again.
+    ///
+    /// # Returns
+    ///
+    /// * `([u8; 6], [u8; 4], [u8; 2])`: The values of the registers from `OFFSET_X_REG_L` (0x45) to
+    ///   `OFFSET_Z_REG_H`, from `CFG_REG_A` (0x60) to `INT_CTRL_REG`, and of `INT_THS_L_REG` (0x65)
+    ///   and `INT_THS_H_REG`.
+    pub fn to_registers(&self) -> ([u8; 6], [u8; 4], [u8; 2]) {
+        let offset = OffsetXYZ {
+            x: self.offset[0],
+            y: self.offset[1],
+            z: self.offset[2],
+        };
+        let reg_a = self.cfg_reg_a.with_soft_rst(0).with_reboot(0);
+        let ths = IntThsReg::new().with_int_ths(self.int_threshold);
+
+        (
+            offset.to_le_bytes(),
+            [
+                reg_a.into_bits(),
+                self.cfg_reg_b.into_bits(),
+                self.cfg_reg_c.into_bits(),
+                self.int_ctrl_reg.into_bits(),
+            ],
+            ths.into_bits().to_le_bytes(),
+        )
+    }
+}
@@ -0,0 +1,113 @@
+use crate::prelude::*;
+use crate::{Error, Iis2mdc, MagneticField, from_lsb_to_celsius};
+use embedded_hal::delay::DelayNs;
+use st_mems_bus::BusOperation;
+
+/// Number of `STATUS_REG` polls per sample period while waiting for new data.
+pub(crate) const SAMPLE_POLLS_PER_PERIOD: u32 = 4;
+
+/// Number of bytes from `STATUS_REG` (0x67) to `TEMP_OUT_H_REG` (0x6F).
+pub const RAW_SAMPLE_LEN: usize = 9;
+
+/// Status, magnetic and temperature output registers read in a single burst.
+///
+/// All the values belong to the same sample, so the temperature can be used to compensate the
+/// field it was read with.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct RawSample {
+    /// Content of the `STATUS_REG` register.
+    pub status: StatusReg,
+    /// Raw X, Y, Z magnetic output values (1 LSB = 1.5 mG).
+    pub magnetic: [i16; 3],
+    /// Raw temperature output value (8 LSB/°C, 0 LSB at 25 °C).
+    pub temperature: i16,
+}
+
+impl RawSample {
+    /// Decodes the registers from `STATUS_REG` to `TEMP_OUT_H_REG`.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes`: The register values, starting from `STATUS_REG` (0x67).
+    pub fn from_bytes(bytes: [u8; RAW_SAMPLE_LEN]) -> Self {
+        let mut out = [0u8; 6];
+        out.copy_from_slice(&bytes[1..7]);
+        let out = OutXYZ::from_le_bytes(out);
+        let temp = TempOutReg::from_bits(u16::from_le_bytes([bytes[7], bytes[8]]));
+
+        Self {
+            status: StatusReg::from_bits(bytes[0]),
+            magnetic: [out.x, out.y, out.z],
+            temperature: temp.temp_out(),
+        }
+    }
+}
+
+/// Magnetic sample produced by [`SampleIter`].
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Sample {
+    /// Magnetic field, in milligauss.
+    pub field: MagneticField,
+    /// Temperature in degrees Celsius, if requested.
+    pub temperature: Option<f32>,
+    /// At least one sample was overwritten by the sensor before this one was read (`zyxor`).
+    pub overrun: bool,
+}
+
+impl Sample {
+    /// Converts a burst of raw registers into a sample in physical units.
+    ///
+    /// # Arguments
+    ///
+    /// * `raw`: The raw registers, as returned by `sample_raw_get`.
+    /// * `temperature`: Whether to keep the temperature.
+    pub fn from_raw(raw: &RawSample, temperature: bool) -> Self {
+        Self {
+            field: MagneticField::from_raw(raw.magnetic),
+            temperature: temperature.then(|| from_lsb_to_celsius(raw.temperature)),
+            overrun: raw.status.zyxor() == 1,
+        }
+    }
+}
+
+/// Blocking iterator over the samples of the sensor, created with `samples`.
+///
+/// Each call to `next` waits for new data, sleeping with the delay of the driver between two
+/// consecutive `STATUS_REG` polls, paced to the output data rate. The iterator never ends: an
+/// error is yielded as `Some(Err(..))`, e.g. `Error::Timeout` if no data is produced within two
+/// sample periods because the device is not in continuous mode.
+pub struct SampleIter<'a, B, T> {
+    pub(crate) sensor: &'a mut Iis2mdc<B, T>,
+    pub(crate) period_ms: u32,
+    pub(crate) temperature: bool,
+}
+
+impl<B: BusOperation, T: DelayNs> SampleIter<'_, B, T> {
+    fn read(&mut self) -> Result<Sample, Error<B::Error>> {
+        let poll_ms = (self.period_ms / SAMPLE_POLLS_PER_PERIOD).max(1);
+        let timeout_ms = 2 * self.period_ms;
+
+        let mut elapsed = 0;
+        while self.sensor.mag_data_ready_get()? == 0 {
+            if elapsed >= timeout_ms {
+                return Err(Error::Timeout);
+            }
+            self.sensor.tim.delay_ms(poll_ms);
+            elapsed += poll_ms;
+        }
+
+        let raw = self.sensor.sample_raw_get()?;
+        Ok(Sample::from_raw(&raw, self.temperature))
+    }
+}
+
+impl<B: BusOperation, T: DelayNs> Iterator for SampleIter<'_, B, T> {
+    type Item = Result<Sample, Error<B::Error>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.read())
+    }
+}
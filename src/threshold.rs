@@ -0,0 +1,147 @@
+use crate::prelude::*;
+use crate::{Error, from_lsb_to_mgauss, from_mgauss_to_lsb};
+
+/// Threshold interrupt generator configuration covering the `INT_CTRL_REG` and `INT_THS_L/H_REG`
+/// registers.
+///
+/// The threshold is symmetric: an event is generated on an enabled axis when its value exceeds
+/// `+threshold_mg` (positive side) or falls below `-threshold_mg` (negative side).
+///
+/// `ThresholdConfig::default()` matches the power-on values of the registers, with the interrupt
+/// generator disabled.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ThresholdConfig {
+    /// Threshold in milligauss. Rounded to the 1.5 mG resolution of the register.
+    pub threshold_mg: f32,
+    /// Interrupt detection on the X-axis.
+    pub xien: u8,
+    /// Interrupt detection on the Y-axis.
+    pub yien: u8,
+    /// Interrupt detection on the Z-axis.
+    pub zien: u8,
+    /// Latched or pulsed interrupt signal.
+    pub iel: Iel,
+    /// Polarity of the interrupt signal.
+    pub iea: Iea,
+    /// Interrupt generator enable.
+    pub ien: u8,
+}
+
+impl Default for ThresholdConfig {
+    fn default() -> Self {
+        Self {
+            threshold_mg: 0.0,
+            xien: 1,
+            yien: 1,
+            zien: 1,
+            iel: Iel::Pulsed,
+            iea: Iea::ActiveLow,
+            ien: 0,
+        }
+    }
+}
+
+impl ThresholdConfig {
+    /// Encodes the configuration into the values of the `INT_CTRL_REG` and `INT_THS_L/H_REG`
+    /// registers.
+    pub fn to_registers(&self) -> (IntCtrlReg, IntThsReg) {
+        let ctrl = IntCtrlReg::new()
+            .with_ien(self.ien)
+            .with_iel(self.iel as u8)
+            .with_iea(self.iea as u8)
+            .with_zien(self.zien)
+            .with_yien(self.yien)
+            .with_xien(self.xien);
+        let ths = IntThsReg::new().with_int_ths(from_mgauss_to_lsb(self.threshold_mg));
+
+        (ctrl, ths)
+    }
+
+    /// Decodes the configuration from the values of the `INT_CTRL_REG` and `INT_THS_L/H_REG`
+    /// registers.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::InvalidRegisterValue`: This error is returned if a field holds a value that cannot be decoded.
+    pub fn from_registers<E>(ctrl: IntCtrlReg, ths: IntThsReg) -> Result<Self, Error<E>> {
+        let invalid = |value: u8| Error::InvalidRegisterValue {
+            reg: Reg::IntCtrlReg,
+            value,
+        };
+
+        Ok(Self {
+            threshold_mg: from_lsb_to_mgauss(ths.int_ths()),
+            xien: ctrl.xien(),
+            yien: ctrl.yien(),
+            zien: ctrl.zien(),
+            iel: Iel::try_from(ctrl.iel()).map_err(|_| invalid(ctrl.iel()))?,
+            iea: Iea::try_from(ctrl.iea()).map_err(|_| invalid(ctrl.iea()))?,
+            ien: ctrl.ien(),
+        })
+    }
+
+    /// Checks that the configuration can be written to the device.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::InvalidConfiguration`: This error is returned if a flag is outside `0..=1`, or
+    ///   if the threshold is negative, not a number or does not fit in the register.
+    pub fn validate<E>(&self) -> Result<(), Error<E>> {
+        let flags = [self.xien, self.yien, self.zien, self.ien];
+        if flags.iter().any(|&flag| flag > 1) {
+            return Err(Error::InvalidConfiguration);
+        }
+
+        if !(0.0..=from_lsb_to_mgauss(i16::MAX)).contains(&self.threshold_mg) {
+            return Err(Error::InvalidConfiguration);
+        }
+
+        Ok(())
+    }
+}
+
+/// Decoded content of the `INT_SOURCE_REG` register.
+///
+/// Axis flags are ordered X, Y, Z.
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub struct ThresholdEvent {
+    /// An interrupt event occurred.
+    pub int: bool,
+    /// The value exceeded the threshold on the positive side.
+    pub positive: [bool; 3],
+    /// The value exceeded the threshold on the negative side.
+    pub negative: [bool; 3],
+    /// Internal measurement range overflow on magnetic data.
+    pub mroi: bool,
+}
+
+impl ThresholdEvent {
+    /// Decodes the event from the value of the `INT_SOURCE_REG` register.
+    pub fn from_register(val: IntSourceReg) -> Self {
+        Self {
+            int: val.int() == 1,
+            positive: [val.p_th_s_x(), val.p_th_s_y(), val.p_th_s_z()].map(|bit| bit == 1),
+            negative: [val.n_th_s_x(), val.n_th_s_y(), val.n_th_s_z()].map(|bit| bit == 1),
+            mroi: val.mroi() == 1,
+        }
+    }
+
+    /// Returns `true` if the threshold was exceeded on either side on the given axis.
+    ///
+    /// # Arguments
+    ///
+    /// * `axis`: The axis index, `0` for X, `1` for Y and `2` for Z.
+    pub fn is_axis_triggered(&self, axis: usize) -> bool {
+        self.positive.get(axis).copied().unwrap_or(false)
+            || self.negative.get(axis).copied().unwrap_or(false)
+    }
+}
+
+impl From<IntSourceReg> for ThresholdEvent {
+    fn from(val: IntSourceReg) -> Self {
+        Self::from_register(val)
+    }
+}
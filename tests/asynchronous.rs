@@ -0,0 +1,249 @@
+//! Host tests of the asynchronous driver against the simulated device.
+
+use core::convert::Infallible;
+use core::pin::pin;
+use core::task::{Context, Poll, Waker};
+use embedded_hal::digital::ErrorType;
+use embedded_hal_async::digital::Wait;
+use iis2mdc_rs::ThresholdConfig;
+use iis2mdc_rs::asynchronous::{BusOperation, Iis2mdc};
+use iis2mdc_rs::prelude::*;
+use iis2mdc_rs::sim::{NoopDelay, SimError, SimIis2mdc};
+use std::cell::RefCell;
+use std::future::{Future, pending};
+use std::rc::Rc;
+
+/// Polls `fut` until it completes, returning `None` if it is still pending after a bounded
+/// number of polls, i.e. it would wait forever on the simulated pin.
+fn run<F: Future>(fut: F) -> Option<F::Output> {
+    let mut fut = pin!(fut);
+    let mut cx = Context::from_waker(Waker::noop());
+    for _ in 0..100 {
+        if let Poll::Ready(out) = fut.as_mut().poll(&mut cx) {
+            return Some(out);
+        }
+    }
+    None
+}
+
+/// Wait call recorded by [`SimPin`].
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum WaitOp {
+    High,
+    Low,
+    RisingEdge,
+    FallingEdge,
+}
+
+/// When [`SimPin`] makes the simulated device produce a sample.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Trigger {
+    /// Just before the wait starts, so an edge is missed.
+    BeforeWait,
+    /// While waiting.
+    DuringWait,
+}
+
+/// Simulated device shared between the driver and [`SimPin`].
+#[derive(Clone)]
+struct SharedSim(Rc<RefCell<SimIis2mdc>>);
+
+impl BusOperation for SharedSim {
+    type Error = SimError;
+
+    async fn read_bytes(&mut self, rbuf: &mut [u8]) -> Result<(), Self::Error> {
+        st_mems_bus::BusOperation::read_bytes(&mut *self.0.borrow_mut(), rbuf)
+    }
+
+    async fn write_bytes(&mut self, wbuf: &[u8]) -> Result<(), Self::Error> {
+        st_mems_bus::BusOperation::write_bytes(&mut *self.0.borrow_mut(), wbuf)
+    }
+
+    async fn write_byte_read_bytes(
+        &mut self,
+        wbuf: &[u8; 1],
+        rbuf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        st_mems_bus::BusOperation::write_byte_read_bytes(&mut *self.0.borrow_mut(), wbuf, rbuf)
+    }
+}
+
+/// GPIO connected to the INT/DRDY pin of the simulated device, recording the wait calls.
+///
+/// The first wait optionally produces a sample. Level waits complete when the pin is at the
+/// level once the sample is produced; edge waits complete only if the sample produced during the
+/// wait changed the level in the expected direction, and never complete otherwise.
+struct SimPin {
+    sim: SharedSim,
+    trigger: Option<Trigger>,
+    waits: Vec<WaitOp>,
+}
+
+impl SimPin {
+    fn new(sim: &SharedSim, trigger: Option<Trigger>) -> Self {
+        Self {
+            sim: sim.clone(),
+            trigger,
+            waits: Vec::new(),
+        }
+    }
+
+    async fn wait(&mut self, op: WaitOp) -> Result<(), Infallible> {
+        self.waits.push(op);
+        let trigger = self.trigger.take();
+        let (before, after) = {
+            let mut sim = self.sim.0.borrow_mut();
+            if trigger == Some(Trigger::BeforeWait) {
+                sim.generate();
+            }
+            let before = sim.int_pin_level();
+            if trigger == Some(Trigger::DuringWait) {
+                sim.generate();
+            }
+            (before, sim.int_pin_level())
+        };
+
+        let ready = match op {
+            WaitOp::High => after,
+            WaitOp::Low => !after,
+            WaitOp::RisingEdge => !before && after,
+            WaitOp::FallingEdge => before && !after,
+        };
+        if !ready {
+            pending::<()>().await;
+        }
+        Ok(())
+    }
+}
+
+impl ErrorType for SimPin {
+    type Error = Infallible;
+}
+
+impl Wait for SimPin {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.wait(WaitOp::High).await
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.wait(WaitOp::Low).await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait(WaitOp::RisingEdge).await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait(WaitOp::FallingEdge).await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        unimplemented!()
+    }
+}
+
+fn sensor() -> (Iis2mdc<SharedSim, NoopDelay>, SharedSim) {
+    let sim = SharedSim(Rc::new(RefCell::new(SimIis2mdc::new())));
+    (Iis2mdc::from_bus(sim.clone(), NoopDelay), sim)
+}
+
+/// Configures the threshold generator at 150 mG on all axes, in continuous mode.
+fn threshold_setup(sensor: &mut Iis2mdc<SharedSim, NoopDelay>, iel: Iel, iea: Iea) {
+    run(async {
+        sensor
+            .threshold_event_set(&ThresholdConfig {
+                threshold_mg: 150.0,
+                iel,
+                iea,
+                ien: 1,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        sensor.operating_mode_set(Md::ContinuousMode).await.unwrap();
+    })
+    .unwrap();
+}
+
+#[test]
+fn latched_event_before_wait_is_not_missed() {
+    let (mut sensor, sim) = sensor();
+    threshold_setup(&mut sensor, Iel::Latched, Iea::ActiveHigh);
+    sim.0.borrow_mut().inject_field([200, 0, 0]);
+
+    // The interrupt latches after INT_SOURCE_REG is read but before the wait starts: the pin
+    // stays high and no edge follows
+    let mut int = SimPin::new(&sim, Some(Trigger::BeforeWait));
+    let event = run(sensor.threshold_event_wait(&mut int))
+        .expect("wait hung on a latched interrupt")
+        .unwrap();
+
+    assert!(event.int);
+    assert_eq!(event.positive, [true, false, false]);
+    assert_eq!(int.waits, vec![WaitOp::High]);
+    assert!(!sim.0.borrow().int_pin_level());
+}
+
+#[test]
+fn latched_active_low_waits_for_low_level() {
+    let (mut sensor, sim) = sensor();
+    threshold_setup(&mut sensor, Iel::Latched, Iea::ActiveLow);
+    sim.0.borrow_mut().inject_field([0, 0, -200]);
+    sim.0.borrow_mut().generate();
+
+    let mut int = SimPin::new(&sim, None);
+    let event = run(sensor.threshold_event_wait(&mut int)).unwrap().unwrap();
+
+    assert_eq!(event.negative, [false, false, true]);
+    assert_eq!(int.waits, vec![WaitOp::Low]);
+    assert!(sim.0.borrow().int_pin_level());
+}
+
+#[test]
+fn pulsed_event_waits_for_edge() {
+    let (mut sensor, sim) = sensor();
+    threshold_setup(&mut sensor, Iel::Pulsed, Iea::ActiveHigh);
+
+    // No sample: the wait does not complete
+    let mut int = SimPin::new(&sim, None);
+    assert!(run(sensor.threshold_event_wait(&mut int)).is_none());
+    assert_eq!(int.waits, vec![WaitOp::RisingEdge]);
+
+    sim.0.borrow_mut().inject_field([0, 200, 0]);
+    let mut int = SimPin::new(&sim, Some(Trigger::DuringWait));
+    let event = run(sensor.threshold_event_wait(&mut int)).unwrap().unwrap();
+
+    assert_eq!(event.positive, [false, true, false]);
+    assert_eq!(int.waits, vec![WaitOp::RisingEdge]);
+}
+
+#[test]
+fn event_enables_interrupt_on_pin() {
+    let (mut sensor, sim) = sensor();
+
+    let mut int = SimPin::new(&sim, None);
+    assert!(run(sensor.threshold_event_wait(&mut int)).is_none());
+
+    let sim = sim.0.borrow();
+    let ctrl = IntCtrlReg::from_bits(sim.register(Reg::IntCtrlReg as u8).unwrap());
+    let reg_c = CfgRegC::from_bits(sim.register(Reg::CfgRegC as u8).unwrap());
+    assert_eq!(ctrl.ien(), 1);
+    assert_eq!(reg_c.int_on_pin(), 1);
+    assert_eq!(int.waits, vec![WaitOp::FallingEdge]);
+}
+
+#[test]
+fn magnetic_raw_wait_reads_sample_on_drdy() {
+    let (mut sensor, sim) = sensor();
+    sim.0.borrow_mut().set_field([10, -20, 30]);
+    run(sensor.operating_mode_set(Md::ContinuousMode))
+        .unwrap()
+        .unwrap();
+
+    let mut drdy = SimPin::new(&sim, Some(Trigger::DuringWait));
+    let raw = run(sensor.magnetic_raw_wait(&mut drdy)).unwrap().unwrap();
+
+    assert_eq!(raw, [10, -20, 30]);
+    assert_eq!(drdy.waits, vec![WaitOp::High]);
+    assert!(!sim.0.borrow().int_pin_level());
+}
@@ -0,0 +1,315 @@
+//! Host tests of the calibration helpers on synthetic samples.
+
+use iis2mdc_rs::calibration::{
+    CALIBRATION_DATA_SIZE, CALIBRATION_DATA_VERSION, CalibrationData, CalibrationDataError,
+    HARD_IRON_MIN_SAMPLES, HardIronCalibrator, HardIronMethod, SOFT_IRON_MIN_SAMPLES,
+    SoftIronCalibrator,
+};
+use std::f64::consts::PI;
+
+/// Returns `n` unit vectors evenly distributed over the sphere (Fibonacci lattice).
+fn sphere_points(n: usize) -> Vec<[f64; 3]> {
+    let golden = PI * (3.0 - 5.0_f64.sqrt());
+    (0..n)
+        .map(|i| {
+            let z = 1.0 - 2.0 * (i as f64 + 0.5) / n as f64;
+            let r = (1.0 - z * z).sqrt();
+            let phi = golden * i as f64;
+            [r * phi.cos(), r * phi.sin(), z]
+        })
+        .collect()
+}
+
+/// Returns the raw sample `matrix * unit + offset`, rounded to LSB.
+fn distort(unit: [f64; 3], matrix: &[[f64; 3]; 3], offset: [f64; 3]) -> [i16; 3] {
+    [0, 1, 2].map(|i| {
+        let v: f64 = (0..3).map(|j| matrix[i][j] * unit[j]).sum();
+        (v + offset[i]).round() as i16
+    })
+}
+
+/// Returns `rotation(z, yaw) * rotation(x, roll) * diag(scale)`.
+fn rotated_scale(yaw: f64, roll: f64, scale: [f64; 3]) -> [[f64; 3]; 3] {
+    let (sy, cy) = yaw.sin_cos();
+    let (sr, cr) = roll.sin_cos();
+    let rot = [
+        [cy, -sy * cr, sy * sr],
+        [sy, cy * cr, -cy * sr],
+        [0.0, sr, cr],
+    ];
+    rot.map(|row| [0, 1, 2].map(|j| row[j] * scale[j]))
+}
+
+fn magnitude(v: [f32; 3]) -> f32 {
+    (v[0] * v[0] + v[1] * v[1] + v[2] * v[2]).sqrt()
+}
+
+#[test]
+fn hard_iron_recovers_sphere_center() {
+    let matrix = rotated_scale(0.0, 0.0, [300.0; 3]);
+    let offset = [150.0, -220.0, 75.0];
+    let mut calibrator = HardIronCalibrator::new();
+    for p in sphere_points(200) {
+        calibrator.add_sample(distort(p, &matrix, offset));
+    }
+
+    for method in [HardIronMethod::LeastSquares, HardIronMethod::MinMax] {
+        let fit = calibrator.fit(method).unwrap();
+        for (fitted, expected) in fit.offset.iter().zip(offset) {
+            assert!((f64::from(*fitted) - expected).abs() <= 2.0, "{method:?}");
+        }
+        assert!((fit.radius - 300.0).abs() < 6.0, "{method:?}");
+    }
+}
+
+#[test]
+fn hard_iron_least_squares_works_with_partial_rotation() {
+    let matrix = rotated_scale(0.0, 0.0, [300.0; 3]);
+    let offset = [-40.0, 90.0, 310.0];
+    let mut calibrator = HardIronCalibrator::new();
+    for p in sphere_points(200).into_iter().filter(|p| p[2] > 0.3) {
+        calibrator.add_sample(distort(p, &matrix, offset));
+    }
+
+    let fit = calibrator.fit(HardIronMethod::LeastSquares).unwrap();
+    for (fitted, expected) in fit.offset.iter().zip(offset) {
+        assert!((f64::from(*fitted) - expected).abs() <= 1.0);
+    }
+    assert!((fit.radius - 300.0).abs() < 2.0);
+}
+
+#[test]
+fn hard_iron_requires_min_samples() {
+    let mut calibrator = HardIronCalibrator::new();
+    for raw in [[300, 0, 0], [0, 300, 0], [0, 0, 300]] {
+        calibrator.add_sample(raw);
+    }
+    assert_eq!(calibrator.sample_count(), HARD_IRON_MIN_SAMPLES - 1);
+    assert!(calibrator.fit(HardIronMethod::LeastSquares).is_none());
+    assert!(calibrator.fit(HardIronMethod::MinMax).is_none());
+
+    calibrator.add_sample([-300, 0, 0]);
+    assert!(calibrator.fit(HardIronMethod::LeastSquares).is_some());
+
+    calibrator.reset();
+    assert_eq!(calibrator.sample_count(), 0);
+    assert!(calibrator.fit(HardIronMethod::MinMax).is_none());
+}
+
+#[test]
+fn hard_iron_rejects_singular_samples() {
+    // The same sample repeated does not describe a sphere
+    let mut calibrator = HardIronCalibrator::new();
+    for _ in 0..10 {
+        calibrator.add_sample([100, -50, 200]);
+    }
+    assert!(calibrator.fit(HardIronMethod::LeastSquares).is_none());
+
+    // Samples in a plane do not fix the center along its normal
+    for z in [0.0, 37.0, -211.0, 3000.0] {
+        let mut calibrator = HardIronCalibrator::new();
+        for i in 0..36 {
+            let angle = f64::from(i * 10).to_radians();
+            let raw = [300.0 * angle.cos() + 150.0, 300.0 * angle.sin(), z];
+            calibrator.add_sample(raw.map(|v| v.round() as i16));
+        }
+        assert!(
+            calibrator.fit(HardIronMethod::LeastSquares).is_none(),
+            "z = {z}"
+        );
+    }
+}
+
+#[test]
+fn hard_iron_accumulates_without_capacity_limit() {
+    let matrix = rotated_scale(0.0, 0.0, [300.0; 3]);
+    let offset = [20.0, 30.0, -40.0];
+    let points = sphere_points(100);
+    let mut calibrator = HardIronCalibrator::new();
+    for _ in 0..1000 {
+        for &p in &points {
+            calibrator.add_sample(distort(p, &matrix, offset));
+        }
+    }
+
+    assert_eq!(calibrator.sample_count(), 100_000);
+    let fit = calibrator.fit(HardIronMethod::LeastSquares).unwrap();
+    assert_eq!(fit.offset, [20, 30, -40]);
+}
+
+#[test]
+fn soft_iron_recovers_rotated_ellipsoid() {
+    let matrix = rotated_scale(0.6, -0.4, [400.0, 300.0, 250.0]);
+    let offset = [120.0, -80.0, 40.0];
+    let samples: Vec<[i16; 3]> = sphere_points(500)
+        .into_iter()
+        .map(|p| distort(p, &matrix, offset))
+        .collect();
+
+    let mut calibrator = SoftIronCalibrator::new();
+    for &raw in &samples {
+        calibrator.add_sample(raw);
+    }
+    let fit = calibrator.fit().unwrap();
+
+    for (fitted, expected) in fit.offset.iter().zip(offset) {
+        assert!((fitted - expected as f32).abs() < 1.0);
+    }
+    // The corrected sphere keeps the volume of the ellipsoid
+    let radius = (400.0_f32 * 300.0 * 250.0).cbrt();
+    assert!((fit.radius - radius).abs() / radius < 0.01);
+    for &raw in &samples {
+        let field = fit.apply(raw);
+        let norm = magnitude([field.x, field.y, field.z]) / 1.5;
+        assert!((norm - fit.radius).abs() / fit.radius < 0.01, "{raw:?}");
+    }
+
+    assert!((0.0..0.01).contains(&fit.residual));
+    assert!((0.9..=1.0).contains(&fit.coverage));
+}
+
+#[test]
+fn soft_iron_metrics_stay_in_range() {
+    let matrix = rotated_scale(-1.1, 0.3, [350.0, 330.0, 360.0]);
+    let mut noisy = SoftIronCalibrator::new();
+    let mut cap = SoftIronCalibrator::new();
+    for (i, p) in sphere_points(400).into_iter().enumerate() {
+        // Deterministic noise of a few percent of the radius
+        let noise = 1.0 + 0.04 * ((i * 7919) % 13) as f64 / 12.0 - 0.02;
+        noisy.add_sample(distort(p.map(|v| v * noise), &matrix, [0.0; 3]));
+        // Upper hemisphere only
+        if p[2] > 0.2 {
+            cap.add_sample(distort(p, &matrix, [0.0; 3]));
+        }
+    }
+
+    let noisy = noisy.fit().unwrap();
+    assert!(noisy.residual > 0.001 && noisy.residual < 0.05);
+    assert!((0.0..=1.0).contains(&noisy.coverage));
+
+    let cap = cap.fit().unwrap();
+    assert!((0.0..0.01).contains(&cap.residual));
+    assert!(cap.coverage >= 0.0 && cap.coverage < noisy.coverage);
+}
+
+#[test]
+fn soft_iron_rejects_planar_rotation() {
+    // Rotation about the Z-axis of the sensor, then about a tilted axis
+    let planes = [
+        (
+            rotated_scale(0.0, 0.0, [300.0, 200.0, 1.0]),
+            [0.0, 0.0, 150.0],
+        ),
+        (
+            rotated_scale(0.7, 0.5, [300.0, 250.0, 280.0]),
+            [50.0, 0.0, -20.0],
+        ),
+    ];
+    for (matrix, offset) in planes {
+        let mut calibrator = SoftIronCalibrator::new();
+        for i in 0..360 {
+            let angle = f64::from(i).to_radians();
+            let unit = [angle.cos(), angle.sin(), 0.0];
+            calibrator.add_sample(distort(unit, &matrix, offset));
+        }
+
+        assert!(calibrator.fit().is_none());
+    }
+}
+
+#[test]
+fn soft_iron_requires_min_samples() {
+    let mut calibrator = SoftIronCalibrator::new();
+    let matrix = rotated_scale(0.0, 0.0, [300.0; 3]);
+    for p in sphere_points(SOFT_IRON_MIN_SAMPLES as usize - 1) {
+        calibrator.add_sample(distort(p, &matrix, [0.0; 3]));
+    }
+    assert!(calibrator.fit().is_none());
+
+    calibrator.reset();
+    assert_eq!(calibrator.sample_count(), 0);
+    assert!(calibrator.fit().is_none());
+}
+
+fn calibration_data() -> CalibrationData {
+    CalibrationData {
+        hard_iron: [-120, 45, 3000],
+        soft_iron: [[1.02, -0.01, 0.03], [-0.01, 0.97, 0.0], [0.03, 0.0, 1.01]],
+        soft_iron_offset: [12.5, -3.0, 0.25],
+        temperature: 23.5,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn calibration_data_round_trip() {
+    let data = calibration_data();
+    let mut buf = [0xFF; CALIBRATION_DATA_SIZE + 4];
+
+    assert_eq!(data.serialize(&mut buf), Ok(CALIBRATION_DATA_SIZE));
+    assert_eq!(buf[0], CALIBRATION_DATA_VERSION);
+    assert_eq!(buf[CALIBRATION_DATA_SIZE..], [0xFF; 4]);
+    assert_eq!(CalibrationData::deserialize(&buf), Ok(data));
+    assert_eq!(
+        CalibrationData::deserialize(&buf[..CALIBRATION_DATA_SIZE]),
+        Ok(data)
+    );
+}
+
+#[test]
+fn calibration_data_detects_corruption() {
+    let mut buf = [0; CALIBRATION_DATA_SIZE];
+    calibration_data().serialize(&mut buf).unwrap();
+
+    for pos in 1..CALIBRATION_DATA_SIZE {
+        let mut corrupted = buf;
+        corrupted[pos] ^= 0x10;
+        assert_eq!(
+            CalibrationData::deserialize(&corrupted),
+            Err(CalibrationDataError::CrcMismatch),
+            "byte {pos}"
+        );
+    }
+
+    // Erased flash
+    assert_eq!(
+        CalibrationData::deserialize(&[0xFF; CALIBRATION_DATA_SIZE]),
+        Err(CalibrationDataError::UnsupportedVersion(0xFF))
+    );
+}
+
+#[test]
+fn calibration_data_rejects_short_buffer() {
+    let mut buf = [0; CALIBRATION_DATA_SIZE - 1];
+
+    assert_eq!(
+        calibration_data().serialize(&mut buf),
+        Err(CalibrationDataError::BufferTooSmall)
+    );
+    assert_eq!(
+        CalibrationData::deserialize(&buf),
+        Err(CalibrationDataError::BufferTooSmall)
+    );
+}
+
+#[test]
+fn calibration_data_checks_version_before_crc() {
+    let mut buf = [0; CALIBRATION_DATA_SIZE];
+    calibration_data().serialize(&mut buf).unwrap();
+
+    // A future layout covers different bytes with its CRC, so the stored CRC does not match
+    buf[0] = CALIBRATION_DATA_VERSION + 1;
+    assert_eq!(
+        CalibrationData::deserialize(&buf),
+        Err(CalibrationDataError::UnsupportedVersion(
+            CALIBRATION_DATA_VERSION + 1
+        ))
+    );
+
+    // Records of the first layout lack the soft-iron offset
+    buf[0] = 1;
+    assert_eq!(
+        CalibrationData::deserialize(&buf),
+        Err(CalibrationDataError::UnsupportedVersion(1))
+    );
+}
@@ -0,0 +1,172 @@
+//! Recording fake bus shared by the host tests.
+
+#![allow(dead_code)]
+
+use iis2mdc_rs::Iis2mdc;
+use iis2mdc_rs::prelude::Reg;
+use iis2mdc_rs::sim::NoopDelay;
+use st_mems_bus::BusOperation;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+
+/// Bus transaction recorded by [`RecordingBus`].
+#[derive(Clone, PartialEq, Debug)]
+pub enum Op {
+    /// Register read starting at `reg`.
+    Read { reg: u8, len: usize },
+    /// Register write starting at `reg`.
+    Write { reg: u8, data: Vec<u8> },
+}
+
+pub fn read(reg: Reg, len: usize) -> Op {
+    Op::Read {
+        reg: reg as u8,
+        len,
+    }
+}
+
+pub fn write(reg: Reg, data: &[u8]) -> Op {
+    Op::Write {
+        reg: reg as u8,
+        data: data.to_vec(),
+    }
+}
+
+/// Fake bus backed by a plain 256-byte memory, recording every transaction.
+///
+/// Reads return the memory content, unless a response was queued for the register with
+/// [`RecordingBus::queue_read`]. Writes update the memory.
+pub struct RecordingBus {
+    pub mem: [u8; 256],
+    pub ops: Vec<Op>,
+    queued: VecDeque<(u8, Vec<u8>)>,
+}
+
+impl RecordingBus {
+    pub fn new() -> Self {
+        Self {
+            mem: [0; 256],
+            ops: Vec::new(),
+            queued: VecDeque::new(),
+        }
+    }
+
+    /// Stores `bytes` in memory starting at `reg`.
+    pub fn set(&mut self, reg: Reg, bytes: &[u8]) {
+        let start = reg as usize;
+        self.mem[start..start + bytes.len()].copy_from_slice(bytes);
+    }
+
+    /// Returns `len` bytes of memory starting at `reg`.
+    pub fn get(&self, reg: Reg, len: usize) -> &[u8] {
+        &self.mem[reg as usize..reg as usize + len]
+    }
+
+    /// Makes the next read of `reg` return `bytes` instead of the memory content.
+    pub fn queue_read(&mut self, reg: Reg, bytes: &[u8]) {
+        self.queued.push_back((reg as u8, bytes.to_vec()));
+    }
+}
+
+impl BusOperation for RecordingBus {
+    type Error = Infallible;
+
+    fn read_bytes(&mut self, _rbuf: &mut [u8]) -> Result<(), Self::Error> {
+        panic!("read without register address");
+    }
+
+    fn write_bytes(&mut self, wbuf: &[u8]) -> Result<(), Self::Error> {
+        let reg = wbuf[0];
+        let data = &wbuf[1..];
+        self.mem[reg as usize..reg as usize + data.len()].copy_from_slice(data);
+        self.ops.push(Op::Write {
+            reg,
+            data: data.to_vec(),
+        });
+        Ok(())
+    }
+
+    fn write_byte_read_bytes(
+        &mut self,
+        wbuf: &[u8; 1],
+        rbuf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        let reg = wbuf[0];
+        match self.queued.front() {
+            Some((queued, _)) if *queued == reg => {
+                let (_, bytes) = self.queued.pop_front().unwrap();
+                rbuf.copy_from_slice(&bytes);
+            }
+            _ => rbuf.copy_from_slice(&self.mem[reg as usize..reg as usize + rbuf.len()]),
+        }
+        self.ops.push(Op::Read {
+            reg,
+            len: rbuf.len(),
+        });
+        Ok(())
+    }
+}
+
+pub type Sensor = Iis2mdc<RecordingBus, NoopDelay>;
+
+pub fn sensor() -> Sensor {
+    Iis2mdc::from_bus(RecordingBus::new(), NoopDelay)
+}
+
+/// Returns the position of a field given its LSB-first position, following the bit order of the
+/// register structs.
+pub fn shift(lsb_shift: u8, width: u8) -> u8 {
+    if cfg!(feature = "bit_order_msb") {
+        8 - lsb_shift - width
+    } else {
+        lsb_shift
+    }
+}
+
+/// Returns the mask of a field given its LSB-first position.
+pub fn mask(lsb_shift: u8, width: u8) -> u8 {
+    (((1u16 << width) - 1) as u8) << shift(lsb_shift, width)
+}
+
+/// Returns a field value at its position given its LSB-first position.
+pub fn field(lsb_shift: u8, width: u8, value: u8) -> u8 {
+    (value << shift(lsb_shift, width)) & mask(lsb_shift, width)
+}
+
+/// Checks that a setter performs a single read-modify-write of `reg` that changes only the given
+/// field, starting from both an all-zero and an all-one register.
+pub fn check_rmw(reg: Reg, lsb_shift: u8, width: u8, value: u8, set: impl Fn(&mut Sensor)) {
+    for initial in [0x00, 0xFF] {
+        let mut sensor = sensor();
+        sensor.bus.set(reg, &[initial]);
+
+        set(&mut sensor);
+
+        let expected = (initial & !mask(lsb_shift, width)) | field(lsb_shift, width, value);
+        assert_eq!(
+            sensor.bus.ops,
+            vec![read(reg, 1), write(reg, &[expected])],
+            "initial value {initial:#04x}"
+        );
+    }
+}
+
+/// Checks that a getter performs a single read of `reg` and decodes the given field, with all
+/// the other bits set.
+pub fn check_get<V: PartialEq + core::fmt::Debug>(
+    reg: Reg,
+    lsb_shift: u8,
+    width: u8,
+    value: u8,
+    expected: V,
+    get: impl Fn(&mut Sensor) -> V,
+) {
+    for others in [0x00, 0xFF] {
+        let mut sensor = sensor();
+        let byte = (others & !mask(lsb_shift, width)) | field(lsb_shift, width, value);
+        sensor.bus.set(reg, &[byte]);
+
+        assert_eq!(get(&mut sensor), expected, "register value {byte:#04x}");
+        assert_eq!(sensor.bus.ops, vec![read(reg, 1)]);
+    }
+}
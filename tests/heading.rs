@@ -0,0 +1,140 @@
+//! Host tests of the tilt-compensated heading on synthetic orientations.
+
+use iis2mdc_rs::{Heading, MagneticField, tilt_compensated_heading};
+
+/// Horizontal and downward components of the local field, in milligauss.
+const HORIZONTAL_MG: f32 = 200.0;
+const DOWN_MG: f32 = 400.0;
+
+const TOLERANCE_DEG: f32 = 0.01;
+
+/// Returns the field and gravity readings of a device with the given heading, pitch and roll,
+/// in degrees, following the conventions of [`Heading`].
+fn readings(heading: f32, pitch: f32, roll: f32) -> (MagneticField, [f32; 3]) {
+    let (sh, ch) = heading.to_radians().sin_cos();
+    let (sp, cp) = pitch.to_radians().sin_cos();
+    let (sr, cr) = roll.to_radians().sin_cos();
+
+    // World frame: X north, Y west, Z up
+    let forward = [cp * ch, -cp * sh, sp];
+    let left_flat = [sh, ch, 0.0];
+    let up_flat = cross(forward, left_flat);
+    // Positive roll raises the left side
+    let left = [0, 1, 2].map(|i| left_flat[i] * cr + up_flat[i] * sr);
+    let up = [0, 1, 2].map(|i| up_flat[i] * cr - left_flat[i] * sr);
+
+    let field = [HORIZONTAL_MG, 0.0, -DOWN_MG];
+    let [x, y, z] = [forward, left, up].map(|axis| dot(axis, field));
+    let gravity = [forward, left, up].map(|axis| axis[2]);
+
+    (MagneticField { x, y, z }, gravity)
+}
+
+fn field(x: f32, y: f32, z: f32) -> MagneticField {
+    MagneticField { x, y, z }
+}
+
+fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+/// Difference between two angles in degrees, in -180..180.
+fn angle_diff(a: f32, b: f32) -> f32 {
+    (a - b + 540.0).rem_euclid(360.0) - 180.0
+}
+
+fn assert_heading(actual: Heading, magnetic: f32, pitch: f32, roll: f32) {
+    assert!(
+        angle_diff(actual.magnetic, magnetic).abs() < TOLERANCE_DEG,
+        "{actual:?}"
+    );
+    assert!((actual.pitch - pitch).abs() < TOLERANCE_DEG, "{actual:?}");
+    assert!(
+        angle_diff(actual.roll, roll).abs() < TOLERANCE_DEG,
+        "{actual:?}"
+    );
+    assert!((0.0..360.0).contains(&actual.magnetic), "{actual:?}");
+    assert!((0.0..360.0).contains(&actual.true_heading), "{actual:?}");
+}
+
+#[test]
+fn flat_cardinal_directions() {
+    let cases = [
+        (field(HORIZONTAL_MG, 0.0, -DOWN_MG), 0.0),
+        (field(0.0, HORIZONTAL_MG, -DOWN_MG), 90.0),
+        (field(-HORIZONTAL_MG, 0.0, -DOWN_MG), 180.0),
+        (field(0.0, -HORIZONTAL_MG, -DOWN_MG), 270.0),
+    ];
+
+    for (mag, expected) in cases {
+        let heading = tilt_compensated_heading(&mag, [0.0, 0.0, 1.0], 0.0).unwrap();
+        assert_heading(heading, expected, 0.0, 0.0);
+        assert_eq!(heading.true_heading, heading.magnetic);
+    }
+}
+
+#[test]
+fn known_pitch_and_roll() {
+    for (heading, pitch, roll) in [
+        (30.0, 20.0, -35.0),
+        (135.0, -40.0, 10.0),
+        (250.0, 5.0, 60.0),
+        (300.0, 60.0, -150.0),
+    ] {
+        let (field, gravity) = readings(heading, pitch, roll);
+        let result = tilt_compensated_heading(&field, gravity, 0.0).unwrap();
+        assert_heading(result, heading, pitch, roll);
+    }
+}
+
+#[test]
+fn gravity_scale_does_not_matter() {
+    let (field, gravity) = readings(75.0, -15.0, 25.0);
+    let g = tilt_compensated_heading(&field, gravity, 0.0).unwrap();
+    // Same reading in mg
+    let mg = tilt_compensated_heading(&field, gravity.map(|v| v * 1000.0), 0.0).unwrap();
+
+    assert_heading(mg, g.magnetic, g.pitch, g.roll);
+}
+
+#[test]
+fn declination_wraps_around() {
+    let (field, gravity) = readings(350.0, 10.0, -5.0);
+    let heading = tilt_compensated_heading(&field, gravity, 15.0).unwrap();
+    assert!((heading.magnetic - 350.0).abs() < TOLERANCE_DEG);
+    assert!((heading.true_heading - 5.0).abs() < TOLERANCE_DEG);
+
+    let (field, gravity) = readings(10.0, 0.0, 0.0);
+    let heading = tilt_compensated_heading(&field, gravity, -20.0).unwrap();
+    assert!((heading.magnetic - 10.0).abs() < TOLERANCE_DEG);
+    assert!((heading.true_heading - 350.0).abs() < TOLERANCE_DEG);
+}
+
+#[test]
+fn degenerate_vectors() {
+    let north = field(HORIZONTAL_MG, 0.0, -DOWN_MG);
+    assert_eq!(tilt_compensated_heading(&north, [0.0; 3], 0.0), None);
+
+    let zero = field(0.0, 0.0, 0.0);
+    assert_eq!(tilt_compensated_heading(&zero, [0.0, 0.0, 1.0], 0.0), None);
+
+    // Field parallel to gravity, e.g. at a magnetic pole: no horizontal component
+    let vertical = field(0.0, 0.0, -DOWN_MG);
+    assert_eq!(
+        tilt_compensated_heading(&vertical, [0.0, 0.0, 1.0], 0.0),
+        None
+    );
+    let (tilted, gravity) = readings(0.0, 30.0, 20.0);
+    let up = gravity.map(|v| v * DOWN_MG);
+    let vertical = field(-up[0], -up[1], -up[2]);
+    assert_eq!(tilt_compensated_heading(&vertical, gravity, 0.0), None);
+    assert!(tilt_compensated_heading(&tilted, gravity, 0.0).is_some());
+}
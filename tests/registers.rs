@@ -0,0 +1,1164 @@
+//! Register-level tests of the blocking driver against a recording fake bus.
+//!
+//! Field positions are given LSB-first, as in the datasheet; the helpers mirror them when the
+//! `bit_order_msb` feature is enabled.
+
+mod common;
+
+use common::*;
+use iis2mdc_rs::calibration::{CalibrationData, HardIronFit, SoftIronFit};
+use iis2mdc_rs::prelude::*;
+use iis2mdc_rs::*;
+
+// CFG_REG_A (0x60)
+
+#[test]
+fn operating_mode() {
+    for (md, bits) in [
+        (Md::ContinuousMode, 0),
+        (Md::SingleTrigger, 1),
+        (Md::PowerDown, 2),
+        (Md::Idle, 3),
+    ] {
+        check_rmw(Reg::CfgRegA, 0, 2, bits, |s| {
+            s.operating_mode_set(md).unwrap()
+        });
+        check_get(Reg::CfgRegA, 0, 2, bits, md as u8, |s| {
+            s.operating_mode_get().unwrap() as u8
+        });
+    }
+}
+
+#[test]
+fn data_rate() {
+    for (odr, bits) in [
+        (Odr::_10hz, 0),
+        (Odr::_20hz, 1),
+        (Odr::_50hz, 2),
+        (Odr::_100hz, 3),
+    ] {
+        check_rmw(Reg::CfgRegA, 2, 2, bits, |s| s.data_rate_set(odr).unwrap());
+        check_get(Reg::CfgRegA, 2, 2, bits, odr as u8, |s| {
+            s.data_rate_get().unwrap() as u8
+        });
+    }
+}
+
+#[test]
+fn data_rate_100hz_changes_only_odr_bits() {
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::CfgRegA, &[0x03]);
+
+    sensor.data_rate_set(Odr::_100hz).unwrap();
+
+    let written = sensor.bus.get(Reg::CfgRegA, 1)[0];
+    assert_eq!(written ^ 0x03, mask(2, 2));
+}
+
+#[test]
+fn power_mode() {
+    for (lp, bits) in [(Lp::HighResolution, 0), (Lp::LowPower, 1)] {
+        check_rmw(Reg::CfgRegA, 4, 1, bits, |s| s.power_mode_set(lp).unwrap());
+        check_get(Reg::CfgRegA, 4, 1, bits, lp as u8, |s| {
+            s.power_mode_get().unwrap() as u8
+        });
+    }
+}
+
+#[test]
+fn reset() {
+    for val in [0, 1] {
+        check_rmw(Reg::CfgRegA, 5, 1, val, |s| s.reset_set(val).unwrap());
+        check_get(Reg::CfgRegA, 5, 1, val, val, |s| s.reset_get().unwrap());
+    }
+}
+
+#[test]
+fn boot() {
+    for val in [0, 1] {
+        check_rmw(Reg::CfgRegA, 6, 1, val, |s| s.boot_set(val).unwrap());
+        check_get(Reg::CfgRegA, 6, 1, val, val, |s| s.boot_get().unwrap());
+    }
+}
+
+#[test]
+fn offset_temp_comp() {
+    for val in [0, 1] {
+        check_rmw(Reg::CfgRegA, 7, 1, val, |s| {
+            s.offset_temp_comp_set(val).unwrap()
+        });
+        check_get(Reg::CfgRegA, 7, 1, val, val, |s| {
+            s.offset_temp_comp_get().unwrap()
+        });
+    }
+}
+
+// CFG_REG_B (0x61)
+
+#[test]
+fn low_pass_bandwidth() {
+    for (lpf, bits) in [(Lpf::OdrDiv2, 0), (Lpf::OdrDiv4, 1)] {
+        check_rmw(Reg::CfgRegB, 0, 1, bits, |s| {
+            s.low_pass_bandwidth_set(lpf).unwrap()
+        });
+        check_get(Reg::CfgRegB, 0, 1, bits, lpf as u8, |s| {
+            s.low_pass_bandwidth_get().unwrap() as u8
+        });
+    }
+}
+
+#[test]
+fn set_rst_mode() {
+    for (set_rst, bits) in [
+        (SetRst::SetSensOdrDiv63, 0),
+        (SetRst::SensOffCancEveryOdr, 1),
+        (SetRst::SetSensOnlyAtPowerOn, 2),
+        (SetRst::SensOffCancOnlyAtPowerOn, 3),
+    ] {
+        check_rmw(Reg::CfgRegB, 1, 2, bits, |s| {
+            s.set_rst_mode_set(set_rst).unwrap()
+        });
+        check_get(Reg::CfgRegB, 1, 2, bits, set_rst as u8, |s| {
+            s.set_rst_mode_get().unwrap() as u8
+        });
+    }
+}
+
+#[test]
+fn offset_int_conf() {
+    for (conf, bits) in [
+        (IntOnDataOff::CheckBefore, 0),
+        (IntOnDataOff::CheckAfter, 1),
+    ] {
+        check_rmw(Reg::CfgRegB, 3, 1, bits, |s| {
+            s.offset_int_conf_set(conf).unwrap()
+        });
+        check_get(Reg::CfgRegB, 3, 1, bits, conf as u8, |s| {
+            s.offset_int_conf_get().unwrap() as u8
+        });
+    }
+}
+
+#[test]
+fn off_canc_en() {
+    for val in [0, 1] {
+        check_rmw(Reg::CfgRegB, 4, 1, val, |s| s.off_canc_en_set(val).unwrap());
+        check_get(Reg::CfgRegB, 4, 1, val, val, |s| {
+            s.off_canc_en_get().unwrap()
+        });
+    }
+}
+
+// CFG_REG_C (0x62)
+
+#[test]
+fn drdy_on_pin() {
+    for val in [0, 1] {
+        check_rmw(Reg::CfgRegC, 0, 1, val, |s| s.drdy_on_pin_set(val).unwrap());
+        check_get(Reg::CfgRegC, 0, 1, val, val, |s| {
+            s.drdy_on_pin_get().unwrap()
+        });
+    }
+}
+
+#[test]
+fn self_test_bit() {
+    for val in [0, 1] {
+        check_rmw(Reg::CfgRegC, 1, 1, val, |s| s.self_test_set(val).unwrap());
+        check_get(Reg::CfgRegC, 1, 1, val, val, |s| s.self_test_get().unwrap());
+    }
+}
+
+#[test]
+fn data_format() {
+    for (ble, bits) in [(Ble::LsbAtLowAdd, 0), (Ble::MsbAtLowAdd, 1)] {
+        check_rmw(Reg::CfgRegC, 3, 1, bits, |s| {
+            s.data_format_set(ble).unwrap()
+        });
+        check_get(Reg::CfgRegC, 3, 1, bits, ble as u8, |s| {
+            s.data_format_get().unwrap() as u8
+        });
+    }
+}
+
+#[test]
+fn block_data_update() {
+    for val in [0, 1] {
+        check_rmw(Reg::CfgRegC, 4, 1, val, |s| {
+            s.block_data_update_set(val).unwrap()
+        });
+        check_get(Reg::CfgRegC, 4, 1, val, val, |s| {
+            s.block_data_update_get().unwrap()
+        });
+    }
+}
+
+#[test]
+fn i2c_interface() {
+    for (i2c_dis, bits) in [(I2cDis::Enable, 0), (I2cDis::Disable, 1)] {
+        check_rmw(Reg::CfgRegC, 5, 1, bits, |s| {
+            s.i2c_interface_set(i2c_dis).unwrap()
+        });
+        check_get(Reg::CfgRegC, 5, 1, bits, i2c_dis as u8, |s| {
+            s.i2c_interface_get().unwrap() as u8
+        });
+    }
+}
+
+#[test]
+fn int_on_pin() {
+    for val in [0, 1] {
+        check_rmw(Reg::CfgRegC, 6, 1, val, |s| s.int_on_pin_set(val).unwrap());
+        check_get(Reg::CfgRegC, 6, 1, val, val, |s| {
+            s.int_on_pin_get().unwrap()
+        });
+    }
+}
+
+// INT_CTRL_REG (0x63), INT_SOURCE_REG (0x64), INT_THS_L/H_REG (0x65, 0x66)
+
+#[test]
+fn int_gen_conf() {
+    let reg = IntCtrlReg::new()
+        .with_ien(1)
+        .with_iel(1)
+        .with_iea(0)
+        .with_zien(0)
+        .with_yien(1)
+        .with_xien(1);
+    let expected = field(0, 1, 1) | field(1, 1, 1) | field(6, 1, 1) | field(7, 1, 1);
+
+    let mut sensor = sensor();
+    sensor.int_gen_conf_set(reg).unwrap();
+    assert_eq!(sensor.bus.ops, vec![write(Reg::IntCtrlReg, &[expected])]);
+
+    let mut sensor = common::sensor();
+    sensor.bus.set(Reg::IntCtrlReg, &[expected]);
+    let read_back = sensor.int_gen_conf_get().unwrap();
+    assert_eq!(read_back.into_bits(), expected);
+    assert_eq!(read_back.iea(), 0);
+    assert_eq!(read_back.zien(), 0);
+    assert_eq!(sensor.bus.ops, vec![read(Reg::IntCtrlReg, 1)]);
+}
+
+#[test]
+fn int_gen_source() {
+    let byte = field(0, 1, 1) | field(1, 1, 1) | field(4, 1, 1) | field(5, 1, 1);
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::IntSourceReg, &[byte]);
+
+    let source = sensor.int_gen_source_get().unwrap();
+
+    assert_eq!(sensor.bus.ops, vec![read(Reg::IntSourceReg, 1)]);
+    assert_eq!(source.int(), 1);
+    assert_eq!(source.mroi(), 1);
+    assert_eq!(source.n_th_s_x(), 1);
+    assert_eq!(source.p_th_s_z(), 1);
+    assert_eq!(
+        [
+            source.n_th_s_y(),
+            source.n_th_s_z(),
+            source.p_th_s_x(),
+            source.p_th_s_y()
+        ],
+        [0; 4]
+    );
+}
+
+#[test]
+fn int_gen_threshold() {
+    let mut sensor = sensor();
+    sensor.int_gen_threshold_set(0x1234).unwrap();
+    assert_eq!(sensor.bus.ops, vec![write(Reg::IntThsLReg, &[0x34, 0x12])]);
+
+    let mut sensor = common::sensor();
+    sensor.bus.set(Reg::IntThsLReg, &[0x30, 0xF8]);
+    assert_eq!(sensor.int_gen_threshold_get().unwrap(), -2000);
+    assert_eq!(sensor.bus.ops, vec![read(Reg::IntThsLReg, 2)]);
+}
+
+#[test]
+fn threshold_event_config() {
+    let config = ThresholdConfig {
+        threshold_mg: 150.0,
+        xien: 1,
+        yien: 0,
+        zien: 1,
+        iel: Iel::Latched,
+        iea: Iea::ActiveHigh,
+        ien: 1,
+    };
+    let ctrl = field(0, 1, 1) | field(1, 1, 1) | field(2, 1, 1) | field(5, 1, 1) | field(7, 1, 1);
+
+    let mut sensor = sensor();
+    sensor.threshold_event_set(&config).unwrap();
+    assert_eq!(
+        sensor.bus.ops,
+        vec![
+            write(Reg::IntThsLReg, &[100, 0]),
+            write(Reg::IntCtrlReg, &[ctrl])
+        ]
+    );
+
+    sensor.bus.ops.clear();
+    assert!(sensor.threshold_event_get().unwrap() == config);
+    assert_eq!(
+        sensor.bus.ops,
+        vec![read(Reg::IntCtrlReg, 1), read(Reg::IntThsLReg, 2)]
+    );
+}
+
+#[test]
+fn threshold_event_rejects_invalid_config() {
+    let mut sensor = sensor();
+    for config in [
+        ThresholdConfig {
+            threshold_mg: -1.0,
+            ..Default::default()
+        },
+        ThresholdConfig {
+            threshold_mg: f32::NAN,
+            ..Default::default()
+        },
+        ThresholdConfig {
+            ien: 2,
+            ..Default::default()
+        },
+    ] {
+        assert!(matches!(
+            sensor.threshold_event_set(&config),
+            Err(Error::InvalidConfiguration)
+        ));
+    }
+    assert!(sensor.bus.ops.is_empty());
+}
+
+#[test]
+fn threshold_event_source() {
+    let byte = field(0, 1, 1) | field(3, 1, 1) | field(7, 1, 1);
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::IntSourceReg, &[byte]);
+
+    let event = sensor.threshold_event_source_get().unwrap();
+
+    assert_eq!(
+        event,
+        ThresholdEvent {
+            int: true,
+            positive: [true, false, false],
+            negative: [false, true, false],
+            mroi: false,
+        }
+    );
+    assert!(event.is_axis_triggered(0));
+    assert!(event.is_axis_triggered(1));
+    assert!(!event.is_axis_triggered(2));
+    assert_eq!(sensor.bus.ops, vec![read(Reg::IntSourceReg, 1)]);
+}
+
+// Hard-iron offset (0x45 to 0x4A)
+
+#[test]
+fn mag_user_offset() {
+    let mut sensor = sensor();
+    sensor.mag_user_offset_set(&[0x0102, -2, 0x7FFF]).unwrap();
+    assert_eq!(
+        sensor.bus.ops,
+        vec![write(
+            Reg::OffsetXRegL,
+            &[0x02, 0x01, 0xFE, 0xFF, 0xFF, 0x7F]
+        )]
+    );
+
+    sensor.bus.ops.clear();
+    assert_eq!(sensor.mag_user_offset_get().unwrap(), [0x0102, -2, 0x7FFF]);
+    assert_eq!(sensor.bus.ops, vec![read(Reg::OffsetXRegL, 6)]);
+}
+
+#[test]
+fn hard_iron_offset_apply() {
+    let mut sensor = sensor();
+    sensor
+        .bus
+        .set(Reg::OffsetXRegL, &[10, 0, 0xF6, 0xFF, 0xFF, 0x7F]);
+    let fit = HardIronFit {
+        offset: [5, -5, 100],
+        radius: 0.0,
+    };
+
+    assert_eq!(
+        sensor.hard_iron_offset_apply(&fit).unwrap(),
+        [15, -15, i16::MAX]
+    );
+    assert_eq!(
+        sensor.bus.ops,
+        vec![
+            read(Reg::OffsetXRegL, 6),
+            write(Reg::OffsetXRegL, &[15, 0, 0xF1, 0xFF, 0xFF, 0x7F])
+        ]
+    );
+}
+
+#[test]
+fn calibration_data() {
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::WhoAmI, &[IIS2MDC_ID]);
+    sensor.bus.set(Reg::OffsetXRegL, &[1, 0, 2, 0, 3, 0]);
+    sensor.bus.set(Reg::TempOutLReg, &[16, 0]);
+    let fit = SoftIronFit {
+        offset: [4.0, -5.0, 6.5],
+        matrix: [[2.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+        radius: 300.0,
+        ..Default::default()
+    };
+
+    let data = sensor.calibration_data_get(&fit).unwrap();
+
+    assert_eq!(
+        data,
+        CalibrationData {
+            sensor_id: IIS2MDC_ID,
+            hard_iron: [1, 2, 3],
+            soft_iron: fit.matrix,
+            soft_iron_offset: fit.offset,
+            temperature: 27.0,
+        }
+    );
+    assert_eq!(
+        data.soft_iron_fit().apply([5, -4, 7]),
+        fit.apply([5, -4, 7])
+    );
+    assert_eq!(
+        sensor.bus.ops,
+        vec![
+            read(Reg::WhoAmI, 1),
+            read(Reg::OffsetXRegL, 6),
+            read(Reg::TempOutLReg, 2)
+        ]
+    );
+
+    let mut sensor = common::sensor();
+    sensor.bus.set(Reg::WhoAmI, &[IIS2MDC_ID]);
+    sensor.restore_calibration(&data).unwrap();
+    assert_eq!(
+        sensor.bus.ops,
+        vec![
+            read(Reg::WhoAmI, 1),
+            write(Reg::OffsetXRegL, &[1, 0, 2, 0, 3, 0])
+        ]
+    );
+
+    let foreign = CalibrationData {
+        sensor_id: 0x3D,
+        ..data
+    };
+    sensor.bus.ops.clear();
+    assert!(matches!(
+        sensor.restore_calibration(&foreign),
+        Err(Error::SensorIdMismatch {
+            stored: 0x3D,
+            device: IIS2MDC_ID
+        })
+    ));
+    assert_eq!(sensor.bus.ops, vec![read(Reg::WhoAmI, 1)]);
+
+    // The stamp is checked against the device, not against the expected ID
+    let mut sensor = common::sensor();
+    sensor.bus.set(Reg::WhoAmI, &[0x3D]);
+    assert!(matches!(
+        sensor.restore_calibration(&data),
+        Err(Error::SensorIdMismatch {
+            stored: IIS2MDC_ID,
+            device: 0x3D
+        })
+    ));
+}
+
+// WHO_AM_I (0x4F), STATUS_REG (0x67) and output registers (0x68 to 0x6F)
+
+#[test]
+fn device_id() {
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::WhoAmI, &[IIS2MDC_ID]);
+
+    assert_eq!(sensor.device_id_get().unwrap(), IIS2MDC_ID);
+    assert_eq!(sensor.bus.ops, vec![read(Reg::WhoAmI, 1)]);
+}
+
+#[test]
+fn status() {
+    check_get(Reg::StatusReg, 3, 1, 1, 1, |s| {
+        s.mag_data_ready_get().unwrap()
+    });
+    check_get(Reg::StatusReg, 3, 1, 0, 0, |s| {
+        s.mag_data_ready_get().unwrap()
+    });
+    check_get(Reg::StatusReg, 7, 1, 1, 1, |s| {
+        s.mag_data_ovr_get().unwrap()
+    });
+    check_get(Reg::StatusReg, 7, 1, 0, 0, |s| {
+        s.mag_data_ovr_get().unwrap()
+    });
+
+    let byte = field(0, 1, 1) | field(3, 1, 1) | field(6, 1, 1);
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::StatusReg, &[byte]);
+    let status = sensor.status_get().unwrap();
+    assert_eq!(
+        [status.xda(), status.yda(), status.zda(), status.zyxda()],
+        [1, 0, 0, 1]
+    );
+    assert_eq!(
+        [status.xor(), status.yor(), status.zor(), status.zyxor()],
+        [0, 0, 1, 0]
+    );
+    assert_eq!(sensor.bus.ops, vec![read(Reg::StatusReg, 1)]);
+}
+
+#[test]
+fn mag_data_ready_wait() {
+    let mut sensor = sensor();
+    sensor.bus.queue_read(Reg::StatusReg, &[0]);
+    sensor.bus.queue_read(Reg::StatusReg, &[0]);
+    sensor.bus.set(Reg::StatusReg, &[field(3, 1, 1)]);
+
+    sensor.mag_data_ready_wait(10).unwrap();
+    assert_eq!(sensor.bus.ops, vec![read(Reg::StatusReg, 1); 3]);
+
+    let mut sensor = common::sensor();
+    assert!(matches!(sensor.mag_data_ready_wait(3), Err(Error::Timeout)));
+    assert_eq!(sensor.bus.ops, vec![read(Reg::StatusReg, 1); 4]);
+}
+
+#[test]
+fn magnetic_data() {
+    let mut sensor = sensor();
+    sensor
+        .bus
+        .set(Reg::OutxLReg, &[0x64, 0x00, 0x9C, 0xFF, 0x00, 0x80]);
+
+    assert_eq!(sensor.magnetic_raw_get().unwrap(), [100, -100, i16::MIN]);
+    assert_eq!(
+        sensor.magnetic_get().unwrap(),
+        MagneticField {
+            x: 150.0,
+            y: -150.0,
+            z: -49152.0
+        }
+    );
+    assert_eq!(sensor.bus.ops, vec![read(Reg::OutxLReg, 6); 2]);
+}
+
+#[test]
+fn temperature_data() {
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::TempOutLReg, &[0xF0, 0xFF]);
+
+    assert_eq!(sensor.temperature_raw_get().unwrap(), -16);
+    assert_eq!(sensor.temperature_get().unwrap(), 23.0);
+    assert_eq!(sensor.bus.ops, vec![read(Reg::TempOutLReg, 2); 2]);
+}
+
+#[test]
+fn sample_raw() {
+    let mut sensor = sensor();
+    let status = field(3, 1, 1) | field(7, 1, 1);
+    sensor
+        .bus
+        .set(Reg::StatusReg, &[status, 1, 0, 2, 0, 0xFD, 0xFF, 8, 0]);
+
+    let raw = sensor.sample_raw_get().unwrap();
+
+    assert_eq!(raw.status.into_bits(), status);
+    assert_eq!(raw.magnetic, [1, 2, -3]);
+    assert_eq!(raw.temperature, 8);
+    assert_eq!(sensor.bus.ops, vec![read(Reg::StatusReg, RAW_SAMPLE_LEN)]);
+
+    let sample = Sample::from_raw(&raw, true);
+    assert_eq!(sample.temperature, Some(26.0));
+    assert!(sample.overrun);
+    assert_eq!(Sample::from_raw(&raw, false).temperature, None);
+}
+
+#[test]
+fn samples_iterator() {
+    let mut sensor = sensor();
+    sensor
+        .bus
+        .set(Reg::CfgRegA, &[field(2, 2, Odr::_50hz as u8)]);
+    sensor.bus.queue_read(Reg::StatusReg, &[0]);
+    sensor
+        .bus
+        .set(Reg::StatusReg, &[field(3, 1, 1), 10, 0, 20, 0, 30, 0, 0, 0]);
+
+    let samples: Vec<_> = sensor
+        .samples(false)
+        .unwrap()
+        .take(2)
+        .map(Result::unwrap)
+        .collect();
+
+    assert_eq!(samples.len(), 2);
+    assert_eq!(
+        samples[0].field,
+        MagneticField {
+            x: 15.0,
+            y: 30.0,
+            z: 45.0
+        }
+    );
+    assert!(!samples[0].overrun);
+    assert_eq!(
+        sensor.bus.ops,
+        vec![
+            read(Reg::CfgRegA, 1),
+            read(Reg::StatusReg, 1),
+            read(Reg::StatusReg, 1),
+            read(Reg::StatusReg, RAW_SAMPLE_LEN),
+            read(Reg::StatusReg, 1),
+            read(Reg::StatusReg, RAW_SAMPLE_LEN),
+        ]
+    );
+}
+
+#[test]
+fn samples_iterator_times_out() {
+    let mut sensor = sensor();
+    sensor
+        .bus
+        .set(Reg::CfgRegA, &[field(2, 2, Odr::_100hz as u8)]);
+
+    let mut samples = sensor.samples(false).unwrap();
+
+    assert!(matches!(samples.next(), Some(Err(Error::Timeout))));
+}
+
+#[test]
+fn sample_fifo_update() {
+    let mut sensor = sensor();
+    let mut fifo: SampleFifo<2> = SampleFifo::new();
+
+    // No new data
+    assert!(!sensor.sample_fifo_update(&mut fifo, 1).unwrap());
+
+    sensor.bus.set(
+        Reg::StatusReg,
+        &[field(3, 1, 1) | field(7, 1, 1), 1, 0, 2, 0, 3, 0],
+    );
+    for timestamp in 2..5 {
+        assert!(sensor.sample_fifo_update(&mut fifo, timestamp).unwrap());
+    }
+
+    assert_eq!(
+        sensor.bus.ops,
+        vec![read(Reg::StatusReg, RAW_SAMPLE_LEN); 4]
+    );
+    assert_eq!(fifo.len(), 2);
+    assert_eq!(fifo.overrun_count(), 3);
+    assert_eq!(fifo.dropped_count(), 1);
+    assert_eq!(fifo.lost_samples(), 4);
+    assert_eq!(
+        fifo.pop(),
+        Some(FifoSample {
+            raw: [1, 2, 3],
+            timestamp: 3,
+            overrun: true
+        })
+    );
+    assert_eq!(fifo.pop().map(|s| s.timestamp), Some(4));
+    assert_eq!(fifo.pop(), None);
+}
+
+// Configuration
+
+fn config_bytes() -> (Config, [u8; 3]) {
+    let config = Config {
+        md: Md::ContinuousMode,
+        odr: Odr::_100hz,
+        lp: Lp::LowPower,
+        comp_temp_en: 1,
+        lpf: Lpf::OdrDiv4,
+        set_rst: SetRst::SensOffCancEveryOdr,
+        int_on_dataoff: IntOnDataOff::CheckAfter,
+        off_canc_one_shot: 1,
+        drdy_on_pin: 1,
+        ble: Ble::MsbAtLowAdd,
+        bdu: 1,
+        i2c_dis: I2cDis::Disable,
+        int_on_pin: 1,
+    };
+    let reg_a = field(0, 2, 0) | field(2, 2, 3) | field(4, 1, 1) | field(7, 1, 1);
+    let reg_b = field(0, 1, 1) | field(1, 2, 1) | field(3, 1, 1) | field(4, 1, 1);
+    let reg_c = field(0, 1, 1) | field(3, 1, 1) | field(4, 1, 1) | field(5, 1, 1) | field(6, 1, 1);
+
+    (config, [reg_a, reg_b, reg_c])
+}
+
+#[test]
+fn apply_and_read_config() {
+    let (config, bytes) = config_bytes();
+
+    let mut sensor = sensor();
+    sensor.apply_config(&config).unwrap();
+    assert_eq!(sensor.bus.ops, vec![write(Reg::CfgRegA, &bytes)]);
+
+    sensor.bus.ops.clear();
+    assert!(sensor.read_config().unwrap() == config);
+    assert_eq!(sensor.bus.ops, vec![read(Reg::CfgRegA, 3)]);
+}
+
+#[test]
+fn default_config_matches_power_on() {
+    let mut sensor = sensor();
+    sensor.apply_config(&Config::default()).unwrap();
+
+    assert_eq!(
+        sensor.bus.ops,
+        vec![write(Reg::CfgRegA, &[field(0, 2, Md::Idle as u8), 0, 0])]
+    );
+    // Unchanged since 1.0.0
+    assert!(Md::default() == Md::ContinuousMode);
+}
+
+#[test]
+fn apply_config_rejects_invalid_config() {
+    let mut sensor = sensor();
+    let invalid = [
+        Config {
+            bdu: 2,
+            ..Default::default()
+        },
+        Config {
+            off_canc_one_shot: 1,
+            set_rst: SetRst::SetSensOnlyAtPowerOn,
+            ..Default::default()
+        },
+    ];
+
+    for config in invalid {
+        assert!(matches!(
+            sensor.apply_config(&config),
+            Err(Error::InvalidConfiguration)
+        ));
+    }
+    assert!(sensor.bus.ops.is_empty());
+}
+
+#[test]
+fn init() {
+    let (config, bytes) = config_bytes();
+    let reset = field(0, 2, 3) | field(5, 1, 1);
+    let reboot = field(0, 2, 3) | field(6, 1, 1);
+
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::WhoAmI, &[IIS2MDC_ID]);
+    sensor.bus.set(Reg::CfgRegA, &[field(0, 2, 3)]);
+    // soft_rst still set on the first poll, then cleared
+    sensor.bus.queue_read(Reg::CfgRegA, &[field(0, 2, 3)]);
+    sensor.bus.queue_read(Reg::CfgRegA, &[reset]);
+    sensor.bus.queue_read(Reg::CfgRegA, &[field(0, 2, 3)]);
+    // reboot cleared on the first poll
+    sensor.bus.queue_read(Reg::CfgRegA, &[field(0, 2, 3)]);
+    sensor.bus.queue_read(Reg::CfgRegA, &[field(0, 2, 3)]);
+
+    sensor.init(&config).unwrap();
+
+    assert_eq!(
+        sensor.bus.ops,
+        vec![
+            read(Reg::WhoAmI, 1),
+            read(Reg::CfgRegA, 1),
+            write(Reg::CfgRegA, &[reset]),
+            read(Reg::CfgRegA, 1),
+            read(Reg::CfgRegA, 1),
+            read(Reg::CfgRegA, 1),
+            write(Reg::CfgRegA, &[reboot]),
+            read(Reg::CfgRegA, 1),
+            write(Reg::CfgRegA, &bytes),
+        ]
+    );
+}
+
+#[test]
+fn init_rejects_wrong_device() {
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::WhoAmI, &[0x3D]);
+
+    assert!(matches!(
+        sensor.init(&Config::default()),
+        Err(Error::InvalidDeviceId(0x3D))
+    ));
+    assert_eq!(sensor.bus.ops, vec![read(Reg::WhoAmI, 1)]);
+}
+
+#[test]
+fn init_rejects_invalid_config_before_reset() {
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::WhoAmI, &[IIS2MDC_ID]);
+    let config = Config {
+        off_canc_one_shot: 1,
+        set_rst: SetRst::SetSensOnlyAtPowerOn,
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        sensor.init(&config),
+        Err(Error::InvalidConfiguration)
+    ));
+    assert!(sensor.bus.ops.is_empty());
+}
+
+#[test]
+fn init_times_out_on_stuck_reset() {
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::WhoAmI, &[IIS2MDC_ID]);
+
+    // The fake bus keeps soft_rst set
+    assert!(matches!(
+        sensor.init(&Config::default()),
+        Err(Error::Timeout)
+    ));
+    let polls = sensor
+        .bus
+        .ops
+        .iter()
+        .filter(|op| **op == read(Reg::CfgRegA, 1))
+        .count();
+    // One read for the read-modify-write, then INIT_MAX_RETRIES + 1 polls
+    assert_eq!(polls, 1 + 11);
+}
+
+#[test]
+fn measure_once() {
+    let reg_b = field(1, 2, SetRst::SensOffCancEveryOdr as u8) | field(4, 1, 1);
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::CfgRegA, &[field(0, 2, 3)]);
+    sensor.bus.set(Reg::OutxLReg, &[1, 0, 2, 0, 3, 0]);
+    // No pending sample, then the triggered one after one poll
+    sensor.bus.queue_read(Reg::StatusReg, &[0]);
+    sensor.bus.queue_read(Reg::StatusReg, &[0]);
+    sensor.bus.set(Reg::StatusReg, &[field(3, 1, 1)]);
+
+    assert_eq!(sensor.measure_once(1).unwrap(), [1, 2, 3]);
+    assert_eq!(
+        sensor.bus.ops,
+        vec![
+            read(Reg::StatusReg, 1),
+            read(Reg::CfgRegB, 1),
+            write(Reg::CfgRegB, &[reg_b]),
+            read(Reg::CfgRegA, 1),
+            write(Reg::CfgRegA, &[field(0, 2, Md::SingleTrigger as u8)]),
+            read(Reg::StatusReg, 1),
+            read(Reg::StatusReg, 1),
+            read(Reg::OutxLReg, 6),
+            write(Reg::CfgRegB, &[0]),
+        ]
+    );
+}
+
+#[test]
+fn measure_once_restores_cfg_reg_b() {
+    let reg_b = field(1, 2, SetRst::SetSensOnlyAtPowerOn as u8) | field(0, 1, 1);
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::CfgRegB, &[reg_b]);
+    // No pending sample, then no sample at all
+    sensor.bus.queue_read(Reg::StatusReg, &[0]);
+    let one_shot =
+        (reg_b & !mask(1, 2)) | field(1, 2, SetRst::SensOffCancEveryOdr as u8) | field(4, 1, 1);
+
+    assert!(matches!(sensor.measure_once(1), Err(Error::Timeout)));
+    assert_eq!(sensor.bus.ops[2], write(Reg::CfgRegB, &[one_shot]));
+    assert_eq!(sensor.bus.ops.last(), Some(&write(Reg::CfgRegB, &[reg_b])));
+    assert_eq!(sensor.bus.get(Reg::CfgRegB, 1), [reg_b]);
+}
+
+#[test]
+fn measure_once_rejects_invalid_flag() {
+    let mut sensor = sensor();
+
+    assert!(matches!(
+        sensor.measure_once(2),
+        Err(Error::InvalidConfiguration)
+    ));
+    assert!(sensor.bus.ops.is_empty());
+}
+
+#[test]
+fn measure_once_discards_pending_sample() {
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::StatusReg, &[field(3, 1, 1)]);
+
+    sensor.measure_once(0).unwrap();
+
+    assert_eq!(
+        sensor.bus.ops[..2],
+        [read(Reg::StatusReg, 1), read(Reg::OutxLReg, 6)]
+    );
+    assert_eq!(sensor.bus.ops[3], write(Reg::CfgRegB, &[0]));
+}
+
+#[test]
+fn self_test_sequence() {
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::StatusReg, &[field(3, 1, 1)]);
+
+    let report = sensor.self_test().unwrap();
+
+    // The output does not change on the fake bus
+    assert_eq!(report.delta, [0.0; 3]);
+    assert!(!report.is_passed());
+    assert!(matches!(report.check::<()>(), Err(Error::SelfTestFailed)));
+
+    let writes: Vec<_> = sensor
+        .bus
+        .ops
+        .iter()
+        .filter(|op| matches!(op, common::Op::Write { .. }))
+        .cloned()
+        .collect();
+    let reg_a = field(0, 2, 0) | field(2, 2, 3) | field(7, 1, 1);
+    let reg_b = field(1, 2, 1);
+    let reg_c = field(4, 1, 1);
+    assert_eq!(
+        writes,
+        vec![
+            write(Reg::CfgRegA, &[reg_a, reg_b, reg_c]),
+            write(Reg::CfgRegC, &[reg_c | field(1, 1, 1)]),
+            write(Reg::CfgRegA, &[0, 0, 0]),
+        ]
+    );
+    let samples = sensor
+        .bus
+        .ops
+        .iter()
+        .filter(|op| **op == read(Reg::OutxLReg, 6))
+        .count();
+    assert_eq!(samples, 2 * (SELF_TEST_SAMPLES + 1));
+}
+
+#[test]
+fn wake_on_magnet() {
+    let preset = WakeOnMagnet::Balanced;
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::StatusReg, &[field(3, 1, 1)]);
+    sensor.bus.set(Reg::OutxLReg, &[100, 0, 0x38, 0xFF, 0, 0]);
+
+    assert_eq!(
+        sensor.wake_on_magnet_set(preset, 300.0).unwrap(),
+        [100, -200, 0]
+    );
+
+    let reg_a = field(0, 2, 0) | field(2, 2, 1) | field(4, 1, 1) | field(7, 1, 1);
+    let reg_b = field(1, 2, 2) | field(3, 1, 1);
+    let reg_c = field(4, 1, 1) | field(6, 1, 1);
+    let ctrl = field(1, 1, 1) | field(2, 1, 1) | field(5, 1, 1) | field(6, 1, 1) | field(7, 1, 1);
+    let writes: Vec<_> = sensor
+        .bus
+        .ops
+        .iter()
+        .filter(|op| matches!(op, common::Op::Write { .. }))
+        .cloned()
+        .collect();
+    assert_eq!(
+        writes,
+        vec![
+            write(Reg::IntThsLReg, &[200, 0]),
+            write(Reg::IntCtrlReg, &[ctrl]),
+            write(Reg::OffsetXRegL, &[0; 6]),
+            write(Reg::CfgRegA, &[reg_a, reg_b, reg_c]),
+            write(Reg::OffsetXRegL, &[100, 0, 0x38, 0xFF, 0, 0]),
+            write(Reg::IntThsLReg, &[200, 0]),
+            write(Reg::IntCtrlReg, &[ctrl | field(0, 1, 1)]),
+        ]
+    );
+    assert_eq!(sensor.bus.ops.last(), Some(&read(Reg::IntSourceReg, 1)));
+}
+
+// Register snapshot
+
+#[test]
+fn dump_registers() {
+    let mut sensor = sensor();
+    sensor.bus.set(Reg::OffsetXRegL, &[1, 0, 0xFE, 0xFF, 3, 0]);
+    sensor.bus.set(Reg::WhoAmI, &[IIS2MDC_ID]);
+    let reg_a = field(0, 2, 0) | field(2, 2, 3);
+    let ctrl = field(0, 1, 1) | field(7, 1, 1);
+    let source = field(0, 1, 1) | field(7, 1, 1);
+    let status = field(3, 1, 1);
+    sensor.bus.set(
+        Reg::CfgRegA,
+        &[
+            reg_a, 0x02, 0x10, ctrl, source, 0x64, 0x00, status, 10, 0, 20, 0, 30, 0, 0xF8, 0xFF,
+        ],
+    );
+
+    let snapshot = sensor.dump_registers().unwrap();
+
+    assert_eq!(
+        sensor.bus.ops,
+        vec![
+            read(Reg::OffsetXRegL, 6),
+            read(Reg::WhoAmI, 1),
+            read(Reg::CfgRegA, SNAPSHOT_BLOCK_LEN)
+        ]
+    );
+    assert_eq!(snapshot.offset, [1, -2, 3]);
+    assert_eq!(snapshot.who_am_i, IIS2MDC_ID);
+    assert_eq!(snapshot.cfg_reg_a.odr(), Odr::_100hz as u8);
+    assert_eq!(snapshot.cfg_reg_a.md(), Md::ContinuousMode as u8);
+    assert_eq!(snapshot.cfg_reg_b.into_bits(), 0x02);
+    assert_eq!(snapshot.cfg_reg_c.into_bits(), 0x10);
+    assert_eq!(snapshot.int_ctrl_reg.xien(), 1);
+    assert_eq!(snapshot.int_ctrl_reg.yien(), 0);
+    assert_eq!(snapshot.int_source_reg.p_th_s_x(), 1);
+    assert_eq!(snapshot.int_threshold, 100);
+    assert_eq!(snapshot.status_reg.zyxda(), 1);
+    assert_eq!(snapshot.magnetic, [10, 20, 30]);
+    assert_eq!(snapshot.temperature, -8);
+
+    let debug = format!("{snapshot:?}");
+    assert!(debug.contains("int_threshold: 100"));
+    assert!(debug.contains("CfgRegA { md: 0, odr: 3"));
+}
+
+#[test]
+fn restore_registers() {
+    let mut sensor = sensor();
+    let reg_a = field(0, 2, 0) | field(2, 2, 2) | field(5, 1, 1) | field(6, 1, 1);
+    let ctrl = field(0, 1, 1) | field(7, 1, 1);
+    sensor.bus.set(Reg::OffsetXRegL, &[1, 0, 0xFE, 0xFF, 3, 0]);
+    sensor.bus.set(
+        Reg::CfgRegA,
+        &[reg_a, 0x02, 0x10, ctrl, 0xFF, 0x9C, 0xFF, 0xFF],
+    );
+    let snapshot = sensor.dump_registers().unwrap();
+
+    let mut sensor = common::sensor();
+    sensor.restore_registers(&snapshot).unwrap();
+
+    assert_eq!(
+        sensor.bus.ops,
+        vec![
+            write(Reg::OffsetXRegL, &[1, 0, 0xFE, 0xFF, 3, 0]),
+            write(Reg::IntThsLReg, &[0x9C, 0xFF]),
+            write(Reg::CfgRegB, &[0x02, 0x10, ctrl]),
+            // The operating mode is restored once the device is fully configured
+            write(Reg::CfgRegA, &[reg_a & !mask(5, 2)]),
+        ]
+    );
+}
+
+// Raw register access
+
+#[test]
+fn raw_register_access() {
+    let mut sensor = sensor();
+    sensor.write_to_register(0x45, &[1, 2, 3]).unwrap();
+    let mut buf = [0u8; 3];
+    sensor.read_from_register(0x45, &mut buf).unwrap();
+
+    assert_eq!(buf, [1, 2, 3]);
+    assert_eq!(
+        sensor.bus.ops,
+        vec![
+            write(Reg::OffsetXRegL, &[1, 2, 3]),
+            read(Reg::OffsetXRegL, 3)
+        ]
+    );
+}
+
+// Formatting
+
+#[test]
+fn debug_output() {
+    assert_eq!(format!("{:?}", Odr::_100hz), "_100hz");
+    assert_eq!(
+        format!("{:?}", SetRst::SensOffCancEveryOdr),
+        "SensOffCancEveryOdr"
+    );
+    assert_eq!(
+        format!("{:?}", CfgRegA::new().with_odr(Odr::_50hz as u8)),
+        "CfgRegA { md: 3, odr: 2, lp: 0, soft_rst: 0, reboot: 0, comp_temp_en: 0 }"
+    );
+    assert_eq!(
+        format!("{:?}", OffsetXYZ { x: 1, y: -2, z: 3 }),
+        "OffsetXYZ { x: 1, y: -2, z: 3 }"
+    );
+    assert!(format!("{:?}", Config::default()).starts_with("Config { md: Idle, odr: _10hz"));
+    assert_eq!(
+        format!("{:?}", Error::<()>::InvalidDeviceId(0x3D)),
+        "InvalidDeviceId(61)"
+    );
+}
+
+/// Collects the output of `ufmt::uwrite!`.
+struct UfmtBuf(String);
+
+impl ufmt::uWrite for UfmtBuf {
+    type Error = core::convert::Infallible;
+
+    fn write_str(&mut self, s: &str) -> Result<(), Self::Error> {
+        self.0.push_str(s);
+        Ok(())
+    }
+}
+
+fn ufmt_debug<T: ufmt::uDebug>(val: &T) -> String {
+    let mut buf = UfmtBuf(String::new());
+    ufmt::uwrite!(buf, "{:?}", val).unwrap();
+    buf.0
+}
+
+#[test]
+fn ufmt_output_matches_debug() {
+    let reg = CfgRegA::new()
+        .with_md(Md::SingleTrigger as u8)
+        .with_comp_temp_en(1);
+    assert_eq!(ufmt_debug(&reg), format!("{reg:?}"));
+    let status = StatusReg::from_bits(0x88);
+    assert_eq!(ufmt_debug(&status), format!("{status:?}"));
+    let offset = OffsetXYZ { x: 1, y: -2, z: 3 };
+    assert_eq!(ufmt_debug(&offset), format!("{offset:?}"));
+    assert_eq!(
+        ufmt_debug(&SetRst::SensOffCancEveryOdr),
+        "SensOffCancEveryOdr"
+    );
+    assert_eq!(
+        ufmt_debug(&Config::default()),
+        format!("{:?}", Config::default())
+    );
+
+    let errors = [
+        Error::Bus(7_u8),
+        Error::InvalidDeviceId(0x3D),
+        Error::SensorIdMismatch {
+            stored: 0x3D,
+            device: IIS2MDC_ID,
+        },
+        Error::InvalidRegisterValue {
+            reg: Reg::CfgRegA,
+            value: 2,
+        },
+        Error::Pin(embedded_hal::digital::ErrorKind::Other),
+        Error::InvalidConfiguration,
+    ];
+    for err in errors {
+        assert_eq!(ufmt_debug(&err), format!("{err:?}"));
+    }
+}
+
+// Conversions
+
+#[test]
+fn conversions() {
+    assert_eq!(from_lsb_to_mgauss(-100), -150.0);
+    assert_eq!(from_lsb_to_celsius(-8), 24.0);
+    assert_eq!(from_lsb_to_ugauss(-3), -4500);
+    assert_eq!(from_lsb_to_ntesla(10), 1500);
+    assert_eq!(from_lsb_to_mcelsius(-8), 24_000);
+    assert_eq!(from_lsb_to_ugauss_xyz([1, -1, 0]), [1500, -1500, 0]);
+    assert_eq!(from_lsb_to_ntesla_xyz([1, -1, 0]), [150, -150, 0]);
+    assert_eq!(from_mgauss_to_lsb(150.0), 100);
+    assert_eq!(from_mgauss_to_lsb(-2.3), -2);
+    assert_eq!(from_mgauss_to_lsb(1.0e9), i16::MAX);
+}
@@ -0,0 +1,267 @@
+//! Tests of the sensor-hub passthrough, with a fake LSM6DSO relaying to the simulated IIS2MDC.
+
+use iis2mdc_rs::prelude::*;
+use iis2mdc_rs::sensor_hub::{Lsm6dsoSensorHub, SensorHubBus, SensorHubError};
+use iis2mdc_rs::sim::{NoopDelay, SimIis2mdc};
+use iis2mdc_rs::{Error, I2CAddress, IIS2MDC_ID, Iis2mdc};
+use st_mems_bus::BusOperation;
+
+const FUNC_CFG_ACCESS: usize = 0x01;
+const CTRL1_XL: usize = 0x10;
+const STATUS_REG: usize = 0x1E;
+const OUTX_L_A: usize = 0x28;
+const STATUS_MASTER_MAINPAGE: usize = 0x39;
+const SENSOR_HUB_1: usize = 0x02;
+const MASTER_CONFIG: usize = 0x14;
+const SLV0_ADD: usize = 0x15;
+const SLV0_SUBADD: usize = 0x16;
+const SLV0_CONFIG: usize = 0x17;
+const DATAWRITE_SLV0: usize = 0x21;
+
+/// LSM6DSO register banks with a sensor hub relaying slave 0 to a simulated IIS2MDC.
+struct FakeImu {
+    user: [u8; 128],
+    hub: [u8; 128],
+    mag: SimIis2mdc,
+    /// Number of sensor-hub cycles run.
+    cycles: usize,
+    /// When set, the accelerometer never produces a sample.
+    stalled: bool,
+    /// Number of bus transfers run.
+    transfers: usize,
+    /// Index of the transfer that fails, if any.
+    fail_at: Option<usize>,
+}
+
+/// Error returned by [`FakeImu`] on the transfer selected with `fail_at`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct BusFault;
+
+impl FakeImu {
+    fn new() -> Self {
+        Self {
+            user: [0; 128],
+            hub: [0; 128],
+            mag: SimIis2mdc::new(),
+            cycles: 0,
+            stalled: false,
+            transfers: 0,
+            fail_at: None,
+        }
+    }
+
+    fn transfer(&mut self) -> Result<(), BusFault> {
+        let index = self.transfers;
+        self.transfers += 1;
+        if self.fail_at == Some(index) {
+            return Err(BusFault);
+        }
+        Ok(())
+    }
+
+    fn bank(&mut self, reg: usize) -> &mut [u8; 128] {
+        if reg != FUNC_CFG_ACCESS && self.user[FUNC_CFG_ACCESS] & 0x40 != 0 {
+            &mut self.hub
+        } else {
+            &mut self.user
+        }
+    }
+
+    fn cycle(&mut self) {
+        self.cycles += 1;
+        self.user[STATUS_REG] |= 0x01;
+        let address = self.hub[SLV0_ADD] >> 1;
+        let reg = self.hub[SLV0_SUBADD];
+        if address != I2CAddress::I2cAdd as u8 {
+            self.user[STATUS_MASTER_MAINPAGE] = 0x08;
+            return;
+        }
+        if self.hub[SLV0_ADD] & 1 == 1 {
+            let len = (self.hub[SLV0_CONFIG] & 0x07) as usize;
+            let mut buf = [0; 7];
+            self.mag
+                .write_byte_read_bytes(&[reg], &mut buf[..len])
+                .unwrap();
+            self.hub[SENSOR_HUB_1..SENSOR_HUB_1 + len].copy_from_slice(&buf[..len]);
+        } else {
+            assert_ne!(
+                self.hub[MASTER_CONFIG] & 0x40,
+                0,
+                "write without WRITE_ONCE"
+            );
+            self.mag
+                .write_bytes(&[reg, self.hub[DATAWRITE_SLV0]])
+                .unwrap();
+        }
+        self.user[STATUS_MASTER_MAINPAGE] = 0x01;
+    }
+}
+
+impl BusOperation for FakeImu {
+    type Error = BusFault;
+
+    fn read_bytes(&mut self, _rbuf: &mut [u8]) -> Result<(), Self::Error> {
+        unimplemented!()
+    }
+
+    fn write_bytes(&mut self, wbuf: &[u8]) -> Result<(), Self::Error> {
+        self.transfer()?;
+        let reg = wbuf[0] as usize;
+        for (i, &byte) in wbuf[1..].iter().enumerate() {
+            self.bank(reg)[reg + i] = byte;
+        }
+        if reg == CTRL1_XL && self.user[FUNC_CFG_ACCESS] == 0 {
+            self.user[STATUS_MASTER_MAINPAGE] = 0;
+            let triggered = self.user[CTRL1_XL] & 0xF0 != 0 && self.hub[MASTER_CONFIG] & 0x04 != 0;
+            if triggered && !self.stalled {
+                self.cycle();
+            }
+        }
+        Ok(())
+    }
+
+    fn write_byte_read_bytes(
+        &mut self,
+        wbuf: &[u8; 1],
+        rbuf: &mut [u8],
+    ) -> Result<(), Self::Error> {
+        self.transfer()?;
+        let reg = wbuf[0] as usize;
+        let len = rbuf.len();
+        rbuf.copy_from_slice(&self.bank(reg)[reg..reg + len]);
+        if reg == OUTX_L_A {
+            self.user[STATUS_REG] &= !0x01;
+        }
+        Ok(())
+    }
+}
+
+fn sensor() -> Iis2mdc<SensorHubBus<Lsm6dsoSensorHub<FakeImu, NoopDelay>>, NoopDelay> {
+    let master = Lsm6dsoSensorHub::new(FakeImu::new(), NoopDelay);
+    Iis2mdc::new_sensor_hub(master, NoopDelay)
+}
+
+fn imu(
+    sensor: &mut Iis2mdc<SensorHubBus<Lsm6dsoSensorHub<FakeImu, NoopDelay>>, NoopDelay>,
+) -> &mut FakeImu {
+    &mut sensor.bus.master.bus
+}
+
+#[test]
+fn reads_through_slave0() {
+    let mut sensor = sensor();
+
+    assert_eq!(sensor.device_id_get().unwrap(), IIS2MDC_ID);
+
+    let imu = imu(&mut sensor);
+    assert_eq!(imu.cycles, 1);
+    assert_eq!(imu.hub[SLV0_ADD], (0x1E << 1) | 1);
+    assert_eq!(imu.hub[SLV0_SUBADD], Reg::WhoAmI as u8);
+    assert_eq!(imu.hub[SLV0_CONFIG] & 0x07, 1);
+}
+
+#[test]
+fn writes_one_byte_per_transaction() {
+    let mut sensor = sensor();
+
+    sensor.mag_user_offset_set(&[1, -2, 3]).unwrap();
+
+    assert_eq!(imu(&mut sensor).cycles, 6);
+    assert_eq!(sensor.mag_user_offset_get().unwrap(), [1, -2, 3]);
+    assert_eq!(imu(&mut sensor).cycles, 6 + 1);
+}
+
+#[test]
+fn splits_long_reads() {
+    let mut sensor = sensor();
+    imu(&mut sensor).mag.inject_field([100, -200, 300]);
+    sensor.operating_mode_set(Md::ContinuousMode).unwrap();
+    imu(&mut sensor).mag.generate();
+    imu(&mut sensor).cycles = 0;
+
+    let raw = sensor.sample_raw_get().unwrap();
+
+    assert_eq!(raw.magnetic, [100, -200, 300]);
+    assert_eq!(imu(&mut sensor).cycles, 2);
+}
+
+#[test]
+fn restores_accelerometer_and_master() {
+    let mut sensor = sensor();
+    imu(&mut sensor).user[CTRL1_XL] = 0x64;
+    sensor.bus.master.pull_up = 1;
+
+    sensor.block_data_update_set(1).unwrap();
+
+    let imu = imu(&mut sensor);
+    assert_eq!(imu.user[CTRL1_XL], 0x64);
+    assert_eq!(imu.user[FUNC_CFG_ACCESS], 0);
+    assert_eq!(imu.hub[MASTER_CONFIG], 0x08);
+    let reg_c = imu.mag.register(Reg::CfgRegC as u8).unwrap();
+    assert_eq!(CfgRegC::from_bits(reg_c).bdu(), 1);
+}
+
+#[test]
+fn reports_nack_and_timeout() {
+    let mut sensor = sensor();
+    sensor.bus.address = 0x1C;
+    assert!(matches!(
+        sensor.device_id_get(),
+        Err(Error::Bus(SensorHubError::Nack))
+    ));
+
+    let mut sensor = self::sensor();
+    imu(&mut sensor).stalled = true;
+    assert!(matches!(
+        sensor.device_id_get(),
+        Err(Error::Bus(SensorHubError::Timeout))
+    ));
+    assert_eq!(imu(&mut sensor).hub[MASTER_CONFIG], 0);
+}
+
+#[test]
+fn restores_imu_after_bus_error() {
+    // Transfers of a one-byte read: configuration of the cycle (0 to 7), wait for the end of the
+    // operation (8, 9), restore (10 to 13), read of SENSOR_HUB_1 (14 to 16)
+    let mut sensor = sensor();
+    sensor.device_id_get().unwrap();
+    assert_eq!(imu(&mut sensor).transfers, 17);
+
+    for fail_at in (0..10).chain([15]) {
+        let mut sensor = self::sensor();
+        let imu = imu(&mut sensor);
+        imu.user[CTRL1_XL] = 0x64;
+        imu.fail_at = Some(fail_at);
+
+        assert!(
+            matches!(
+                sensor.device_id_get(),
+                Err(Error::Bus(SensorHubError::Bus(BusFault)))
+            ),
+            "transfer {fail_at}"
+        );
+
+        let imu = self::imu(&mut sensor);
+        assert_eq!(imu.user[FUNC_CFG_ACCESS], 0, "transfer {fail_at}");
+        assert_eq!(imu.user[CTRL1_XL], 0x64, "transfer {fail_at}");
+        assert_eq!(imu.hub[MASTER_CONFIG], 0, "transfer {fail_at}");
+    }
+}
+
+#[test]
+fn restore_continues_after_bus_error() {
+    // The switch to the sensor hub bank fails: the master cannot be turned off, but the
+    // accelerometer is still restored and the error reported
+    let mut sensor = sensor();
+    let imu = imu(&mut sensor);
+    imu.user[CTRL1_XL] = 0x64;
+    imu.fail_at = Some(10);
+
+    assert!(matches!(
+        sensor.device_id_get(),
+        Err(Error::Bus(SensorHubError::Bus(BusFault)))
+    ));
+    let imu = self::imu(&mut sensor);
+    assert_eq!(imu.user[FUNC_CFG_ACCESS], 0);
+    assert_eq!(imu.user[CTRL1_XL], 0x64);
+}
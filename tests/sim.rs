@@ -0,0 +1,235 @@
+use iis2mdc_rs::prelude::*;
+use iis2mdc_rs::sim::{NoopDelay, SIM_SELF_TEST_DELTA_MG, SimIis2mdc};
+use iis2mdc_rs::*;
+
+fn sensor() -> Iis2mdc<SimIis2mdc, NoopDelay> {
+    Iis2mdc::from_bus(SimIis2mdc::new(), NoopDelay)
+}
+
+#[test]
+fn power_on_values() {
+    let mut sensor = sensor();
+
+    assert_eq!(sensor.device_id_get().unwrap(), IIS2MDC_ID);
+    assert!(sensor.read_config().unwrap() == Config::default());
+    assert_eq!(
+        sensor.int_gen_conf_get().unwrap().into_bits(),
+        IntCtrlReg::new().into_bits()
+    );
+}
+
+#[test]
+fn read_only_bits_ignore_writes() {
+    let mut sensor = sensor();
+
+    sensor
+        .write_to_register(Reg::WhoAmI as u8, &[0x00])
+        .unwrap();
+    sensor
+        .write_to_register(Reg::StatusReg as u8, &[0xFF])
+        .unwrap();
+    sensor
+        .write_to_register(Reg::CfgRegB as u8, &[0xFF])
+        .unwrap();
+
+    assert_eq!(sensor.device_id_get().unwrap(), IIS2MDC_ID);
+    assert_eq!(sensor.bus.register(Reg::StatusReg as u8), Some(0));
+    let reg_b = CfgRegB::new()
+        .with_lpf(1)
+        .with_set_rst(0b11)
+        .with_int_on_dataoff(1)
+        .with_off_canc_one_shot(1);
+    assert_eq!(
+        sensor.bus.register(Reg::CfgRegB as u8),
+        Some(reg_b.into_bits())
+    );
+}
+
+#[test]
+fn soft_reset_restores_defaults() {
+    let mut sensor = sensor();
+    sensor.data_rate_set(Odr::_100hz).unwrap();
+    sensor.mag_user_offset_set(&[1, 2, 3]).unwrap();
+
+    sensor.reset_set(1).unwrap();
+
+    assert_eq!(sensor.reset_get().unwrap(), 0);
+    assert!(sensor.data_rate_get().unwrap() == Odr::_10hz);
+    assert_eq!(sensor.mag_user_offset_get().unwrap(), [0; 3]);
+}
+
+#[test]
+fn init_sequence_completes() {
+    let mut sensor = sensor();
+    let config = Config {
+        md: Md::ContinuousMode,
+        odr: Odr::_50hz,
+        bdu: 1,
+        ..Default::default()
+    };
+
+    sensor.init(&config).unwrap();
+
+    assert!(sensor.read_config().unwrap() == config);
+}
+
+#[test]
+fn data_generation_sets_and_clears_status() {
+    let mut sensor = sensor();
+    sensor.bus.set_auto_generate(false);
+    sensor.bus.set_field([10, -20, 30]);
+
+    // Idle mode produces no data
+    assert!(!sensor.bus.generate());
+    assert_eq!(sensor.mag_data_ready_get().unwrap(), 0);
+
+    sensor.operating_mode_set(Md::ContinuousMode).unwrap();
+    assert!(sensor.bus.generate());
+    assert!(sensor.bus.generate());
+    assert_eq!(sensor.mag_data_ready_get().unwrap(), 1);
+    assert_eq!(sensor.mag_data_ovr_get().unwrap(), 1);
+
+    assert_eq!(sensor.magnetic_raw_get().unwrap(), [10, -20, 30]);
+    assert_eq!(sensor.mag_data_ready_get().unwrap(), 0);
+    assert_eq!(sensor.mag_data_ovr_get().unwrap(), 0);
+}
+
+#[test]
+fn injected_fields_are_consumed_in_order() {
+    let mut sensor = sensor();
+    sensor.bus.set_temperature(16);
+    sensor.bus.inject_field([1, 1, 1]);
+    sensor.bus.inject_field([2, 2, 2]);
+    sensor.operating_mode_set(Md::ContinuousMode).unwrap();
+
+    for expected in [[1, 1, 1], [2, 2, 2], [2, 2, 2]] {
+        sensor.mag_data_ready_wait(1).unwrap();
+        let raw = sensor.sample_raw_get().unwrap();
+        assert_eq!(raw.magnetic, expected);
+        assert_eq!(raw.temperature, 16);
+    }
+    assert_eq!(sensor.bus.pending_fields(), 0);
+}
+
+#[test]
+fn single_measurement_returns_to_idle() {
+    let mut sensor = sensor();
+    sensor.bus.set_field([100, 200, 300]);
+
+    assert_eq!(sensor.measure_once(0).unwrap(), [100, 200, 300]);
+    assert!(sensor.operating_mode_get().unwrap() == Md::Idle);
+}
+
+#[test]
+fn hard_iron_offset_is_subtracted() {
+    let mut sensor = sensor();
+    sensor.bus.set_field([100, 200, 300]);
+    sensor.mag_user_offset_set(&[10, 20, 30]).unwrap();
+
+    assert_eq!(sensor.measure_once(0).unwrap(), [90, 180, 270]);
+}
+
+#[test]
+fn self_test_passes() {
+    let mut sensor = sensor();
+    sensor.bus.set_field([200, -100, 300]);
+
+    let report = sensor.self_test().unwrap();
+
+    assert!(report.is_passed());
+    for delta in report.delta {
+        assert!((delta - SIM_SELF_TEST_DELTA_MG).abs() < 1.5);
+    }
+}
+
+#[test]
+fn latched_interrupt_clears_on_read() {
+    let mut sensor = sensor();
+    sensor
+        .threshold_event_set(&ThresholdConfig {
+            threshold_mg: 150.0,
+            iel: Iel::Latched,
+            iea: Iea::ActiveHigh,
+            ien: 1,
+            ..Default::default()
+        })
+        .unwrap();
+    sensor.int_on_pin_set(1).unwrap();
+    sensor.operating_mode_set(Md::ContinuousMode).unwrap();
+    sensor.bus.inject_field([200, 0, -200]);
+    sensor.bus.inject_field([0, 0, 0]);
+
+    sensor.bus.generate();
+    sensor.bus.generate();
+    assert!(sensor.bus.int_pin_level());
+
+    let event = sensor.threshold_event_source_get().unwrap();
+    assert!(event.int);
+    assert_eq!(event.positive, [true, false, false]);
+    assert_eq!(event.negative, [false, false, true]);
+
+    assert_eq!(
+        sensor.threshold_event_source_get().unwrap(),
+        ThresholdEvent::default()
+    );
+    assert!(!sensor.bus.int_pin_level());
+}
+
+#[test]
+fn pulsed_interrupt_follows_last_sample() {
+    let mut sensor = sensor();
+    sensor
+        .threshold_event_set(&ThresholdConfig {
+            threshold_mg: 150.0,
+            ien: 1,
+            ..Default::default()
+        })
+        .unwrap();
+    sensor.operating_mode_set(Md::ContinuousMode).unwrap();
+    sensor.bus.inject_field([0, 200, 0]);
+    sensor.bus.inject_field([0, 0, 0]);
+
+    sensor.bus.generate();
+    let event = sensor.threshold_event_source_get().unwrap();
+    assert_eq!(event.positive, [false, true, false]);
+    assert_eq!(sensor.threshold_event_source_get().unwrap(), event);
+
+    sensor.bus.generate();
+    assert!(!sensor.threshold_event_source_get().unwrap().int);
+}
+
+#[test]
+fn out_of_map_access_fails() {
+    let mut sensor = sensor();
+    let mut buf = [0u8; 2];
+
+    assert!(sensor.read_from_register(0x44, &mut buf).is_err());
+    assert!(
+        sensor
+            .read_from_register(Reg::TempOutHReg as u8, &mut buf)
+            .is_err()
+    );
+}
+
+#[test]
+fn snapshot_survives_reset() {
+    let mut sensor = sensor();
+    let config = Config {
+        md: Md::ContinuousMode,
+        odr: Odr::_50hz,
+        bdu: 1,
+        ..Default::default()
+    };
+    sensor.apply_config(&config).unwrap();
+    sensor.mag_user_offset_set(&[10, -20, 30]).unwrap();
+    sensor.int_gen_threshold_set(200).unwrap();
+    let snapshot = sensor.dump_registers().unwrap();
+
+    sensor.reset_set(1).unwrap();
+    assert!(sensor.read_config().unwrap() == Config::default());
+    sensor.restore_registers(&snapshot).unwrap();
+
+    assert!(sensor.read_config().unwrap() == config);
+    assert_eq!(sensor.mag_user_offset_get().unwrap(), [10, -20, 30]);
+    assert_eq!(sensor.int_gen_threshold_get().unwrap(), 200);
+}